@@ -0,0 +1,151 @@
+pub mod track;
+
+pub use track::{Interpolation, Keyframe, Track};
+
+use crate::world::scene_node::NodeId;
+use glam::Vec3;
+
+/// Animated value of a node, a light or the camera. Lights are referred to by their index in the
+/// light manager.
+pub enum AnimationTrack {
+    NodePosition(NodeId, Track<Vec3>),
+    /// Degrees around the x, then the y and then the z axis
+    NodeRotation(NodeId, Track<Vec3>),
+    NodeScale(NodeId, Track<Vec3>),
+    LightColor(usize, Track<Vec3>),
+    LightIntensity(usize, Track<f32>),
+    CameraPosition(Track<Vec3>),
+    /// Point the camera looks at
+    CameraTarget(Track<Vec3>),
+    /// Vertical field of view in degrees
+    CameraFov(Track<f32>),
+}
+
+impl AnimationTrack {
+    pub fn get_end_time(&self) -> f32 {
+        match self {
+            Self::NodePosition(_, track)
+            | Self::NodeRotation(_, track)
+            | Self::NodeScale(_, track)
+            | Self::LightColor(_, track)
+            | Self::CameraPosition(track)
+            | Self::CameraTarget(track) => track.get_end_time(),
+            Self::LightIntensity(_, track) | Self::CameraFov(track) => track.get_end_time(),
+        }
+    }
+}
+
+/// Timeline of a scene that plays its tracks back, it can be paused and scrubbed to any time.
+pub struct Animation {
+    tracks: Vec<AnimationTrack>,
+    /// In seconds, from 0 to the duration
+    time: f32,
+    duration: f32,
+    is_playing: bool,
+    is_looping: bool,
+    // time the tracks were last applied at, `None` until they were applied for the first time
+    applied_time: Option<f32>,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation {
+    pub const fn new() -> Self {
+        Self {
+            tracks: Vec::new(),
+            time: 0.0,
+            duration: 0.0,
+            is_playing: true,
+            is_looping: true,
+            applied_time: None,
+        }
+    }
+
+    /// Also makes the animation at least as long as the track.
+    pub fn add_track(&mut self, track: AnimationTrack) {
+        self.duration = self.duration.max(track.get_end_time());
+        self.tracks.push(track);
+        self.applied_time = None;
+    }
+
+    pub fn get_tracks(&self) -> &[AnimationTrack] {
+        &self.tracks
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    pub const fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jumps to `time`, clamped to the length of the animation.
+    pub const fn set_time(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.duration);
+    }
+
+    /// Moves the time by `offset` seconds, forward or backward.
+    pub fn scrub(&mut self, offset: f32) {
+        self.set_time(self.time + offset);
+        println!("animation time: {:.2}s of {:.2}s", self.time, self.duration);
+    }
+
+    pub const fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub const fn set_duration(&mut self, duration: f32) {
+        self.duration = duration.max(0.0);
+        self.time = self.time.min(self.duration);
+    }
+
+    pub const fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+
+    pub const fn set_playing(&mut self, is_playing: bool) {
+        self.is_playing = is_playing;
+    }
+
+    pub const fn toggle_playback(&mut self) {
+        self.is_playing = !self.is_playing;
+    }
+
+    pub const fn set_looping(&mut self, is_looping: bool) {
+        self.is_looping = is_looping;
+    }
+
+    /// Moves the time forward while playing. Looping animations start over at the end, the
+    /// others stop there.
+    pub fn advance(&mut self, delta_time: f32) {
+        if !self.is_playing || self.duration <= 0.0 {
+            return;
+        }
+
+        self.time += delta_time;
+        if self.time >= self.duration {
+            if self.is_looping {
+                self.time %= self.duration;
+            } else {
+                self.time = self.duration;
+                self.is_playing = false;
+            }
+        }
+    }
+
+    /// Returns the time the tracks have to be applied at, `None` when nothing changed since they
+    /// were last applied.
+    pub fn take_time_to_apply(&mut self) -> Option<f32> {
+        if self.tracks.is_empty() || self.applied_time == Some(self.time) {
+            return None;
+        }
+
+        self.applied_time = Some(self.time);
+        Some(self.time)
+    }
+}
@@ -0,0 +1,179 @@
+use std::ops::{Add, Mul, Sub};
+
+/// Values that can be blended between keyframes, such as `f32` and `Vec3`.
+pub trait Animatable:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self>
+{
+}
+
+impl<T> Animatable for T where T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Interpolation {
+    /// Constant speed towards the next keyframe
+    #[default]
+    Linear,
+    /// Smooth curve through the keyframes that eases in and out of the first and last one
+    Bezier,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe<T> {
+    /// In seconds
+    pub time: f32,
+    pub value: T,
+    /// How to get from this keyframe to the next one
+    pub interpolation: Interpolation,
+}
+
+impl<T> Keyframe<T> {
+    pub const fn new(time: f32, value: T, interpolation: Interpolation) -> Self {
+        Self { time, value, interpolation }
+    }
+}
+
+/// Keyframes of one animated value, holding the value of the first and last keyframe before and
+/// after them.
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Animatable> Track<T> {
+    /// Sorts the keyframes by time, there has to be at least one.
+    pub fn new(mut keyframes: Vec<Keyframe<T>>) -> Self {
+        assert!(!keyframes.is_empty(), "an animation track needs at least one keyframe");
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes }
+    }
+
+    pub fn get_keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// Time of the last keyframe
+    pub fn get_end_time(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    pub fn sample(&self, time: f32) -> T {
+        let next_index = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next_index == 0 {
+            return self.keyframes[0].value;
+        }
+        if next_index == self.keyframes.len() {
+            return self.keyframes[next_index - 1].value;
+        }
+
+        let start = &self.keyframes[next_index - 1];
+        let end = &self.keyframes[next_index];
+        let duration = end.time - start.time;
+        let t = (time - start.time) / duration;
+
+        match start.interpolation {
+            Interpolation::Linear => lerp(start.value, end.value, t),
+            Interpolation::Bezier => {
+                // The handles follow the slope through the neighboring keyframes so the curve
+                // passes through every keyframe without a kink
+                let start_handle = start.value + self.get_slope(next_index - 1) * (duration / 3.0);
+                let end_handle = end.value - self.get_slope(next_index) * (duration / 3.0);
+
+                // De Casteljau's algorithm
+                let first = lerp(start.value, start_handle, t);
+                let second = lerp(start_handle, end_handle, t);
+                let third = lerp(end_handle, end.value, t);
+                lerp(lerp(first, second, t), lerp(second, third, t), t)
+            }
+        }
+    }
+
+    /// Change per second at a keyframe, 0 at the first and last keyframe.
+    fn get_slope(&self, index: usize) -> T {
+        let value = self.keyframes[index].value;
+        if index == 0 || index + 1 == self.keyframes.len() {
+            return value * 0.0;
+        }
+
+        let previous = &self.keyframes[index - 1];
+        let next = &self.keyframes[index + 1];
+        (next.value - previous.value) * (1.0 / (next.time - previous.time).max(f32::EPSILON))
+    }
+}
+
+fn lerp<T: Animatable>(start: T, end: T, t: f32) -> T {
+    start + (end - start) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(keys: &[(f32, f32)], interpolation: Interpolation) -> Track<f32> {
+        Track::new(
+            keys.iter().map(|&(time, value)| Keyframe::new(time, value, interpolation)).collect(),
+        )
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn holds_the_first_value_before_the_first_keyframe() {
+        let track = track(&[(1.0, 2.0), (2.0, 4.0)], Interpolation::Linear);
+
+        assert_close(track.sample(0.0), 2.0);
+        assert_close(track.sample(-10.0), 2.0);
+    }
+
+    #[test]
+    fn holds_the_last_value_after_the_last_keyframe() {
+        let track = track(&[(1.0, 2.0), (2.0, 4.0)], Interpolation::Linear);
+
+        assert_close(track.sample(2.5), 4.0);
+        assert_close(track.sample(100.0), 4.0);
+    }
+
+    #[test]
+    fn hits_the_keyframes_at_their_time() {
+        // Given out of order, the track sorts them
+        let track = track(&[(2.0, 1.0), (0.0, 3.0), (1.0, -1.0)], Interpolation::Linear);
+
+        assert_close(track.sample(0.0), 3.0);
+        assert_close(track.sample(1.0), -1.0);
+        assert_close(track.sample(2.0), 1.0);
+        assert_close(track.sample(0.5), 1.0);
+    }
+
+    #[test]
+    fn jumps_at_keyframes_with_the_same_time() {
+        for interpolation in [Interpolation::Linear, Interpolation::Bezier] {
+            let track = track(&[(0.0, 0.0), (1.0, 0.0), (1.0, 5.0), (2.0, 5.0)], interpolation);
+
+            // The later keyframe takes over from its time on
+            assert_close(track.sample(1.0), 5.0);
+            assert!(track.sample(0.999).abs() < 0.01);
+            for i in 0u8..=20 {
+                assert!(track.sample(f32::from(i) / 10.0).is_finite());
+            }
+        }
+
+        let track = track(&[(0.0, 1.0), (1.0, 2.0), (1.0, 4.0)], Interpolation::Linear);
+        assert_close(track.sample(1.0), 4.0);
+    }
+
+    #[test]
+    fn bezier_passes_through_the_keyframes() {
+        let keys = [(0.0, 0.0), (1.0, 2.0), (3.0, 1.0), (4.0, 6.0)];
+        let track = track(&keys, Interpolation::Bezier);
+
+        for (time, value) in keys {
+            assert_close(track.sample(time), value);
+        }
+        // Continuous on both sides of the inner keyframes
+        assert!((track.sample(1.0 - 1e-4) - 2.0).abs() < 1e-3);
+        assert!((track.sample(1.0 + 1e-4) - 2.0).abs() < 1e-3);
+        // Eases in and out of the first and last keyframe
+        assert!(track.sample(0.05) < 0.05);
+        assert!(track.sample(3.95) > 5.95);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::math::tone_mapping::linear_to_srgb;
+use glam::Vec3;
+use std::{fmt, str::FromStr};
+
+pub type RGBColor = Vec3;
+
+/// Arbitrary output variable: what the renderer stores in its framebuffer. Besides the final
+/// image these are the passes used for compositing and denoising. Geometric passes describe the
+/// surface seen through the pixel and are 0 where the camera ray hits nothing.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Aov {
+    /// The rendered image
+    Beauty,
+    /// Distance from the camera to the surface in world units
+    Depth,
+    /// World space normal facing the camera
+    Normal,
+    /// Reflectance of the material without any lighting
+    Albedo,
+    /// Starts at 1 for the first object of the scene
+    ObjectId,
+    /// Starts at 1 for the first material that was created
+    MaterialId,
+    /// Texture coordinates in the red and green channel
+    Uv,
+    /// Light reaching the first surface straight from the lights, plus lights seen by the camera
+    Direct,
+    /// Everything else: reflections, refractions and light bounced off other surfaces
+    Indirect,
+    /// Share of the direct light at the first surface that is blocked by other objects
+    ShadowMask,
+}
+
+impl Aov {
+    pub const ALL: [Self; 10] = [
+        Self::Beauty,
+        Self::Depth,
+        Self::Normal,
+        Self::Albedo,
+        Self::ObjectId,
+        Self::MaterialId,
+        Self::Uv,
+        Self::Direct,
+        Self::Indirect,
+        Self::ShadowMask,
+    ];
+
+    /// Whether the pass holds light, which is displayed through exposure and tone mapping.
+    pub const fn is_radiance(self) -> bool {
+        matches!(self, Self::Beauty | Self::Direct | Self::Indirect)
+    }
+
+    /// Maps a value of a pass that doesn't hold light to a displayable color in [0, 1].
+    pub fn visualize(self, value: RGBColor) -> RGBColor {
+        const DEPTH_FALLOFF: f32 = 0.05;
+
+        match self {
+            Self::Beauty | Self::Direct | Self::Indirect | Self::ShadowMask => {
+                value.clamp(RGBColor::ZERO, RGBColor::ONE)
+            }
+            // Near surfaces are bright, the background stays black
+            Self::Depth if value.x > 0.0 => {
+                RGBColor::splat(1.0 / value.x.mul_add(DEPTH_FALLOFF, 1.0))
+            }
+            Self::Normal if value != RGBColor::ZERO => value * 0.5 + 0.5,
+            Self::Albedo => RGBColor::from_array(
+                value.clamp(RGBColor::ZERO, RGBColor::ONE).to_array().map(linear_to_srgb),
+            ),
+            Self::ObjectId | Self::MaterialId if value.x > 0.0 => get_id_color(value.x),
+            Self::Uv => (value - value.floor()) * RGBColor::new(1.0, 1.0, 0.0),
+            _ => RGBColor::ZERO,
+        }
+    }
+
+    /// The next pass, for cycling through them at runtime.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Beauty => Self::Depth,
+            Self::Depth => Self::Normal,
+            Self::Normal => Self::Albedo,
+            Self::Albedo => Self::ObjectId,
+            Self::ObjectId => Self::MaterialId,
+            Self::MaterialId => Self::Uv,
+            Self::Uv => Self::Direct,
+            Self::Direct => Self::Indirect,
+            Self::Indirect => Self::ShadowMask,
+            Self::ShadowMask => Self::Beauty,
+        }
+    }
+}
+
+/// Distinct, stable color for an ID so neighbouring IDs are easy to tell apart.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss)]
+fn get_id_color(id: f32) -> RGBColor {
+    let mut hash = (id as u32).wrapping_mul(0x9E37_79B9);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x85EB_CA6B);
+    hash ^= hash >> 13;
+    let [r, g, b, _] = hash.to_le_bytes();
+    // Keeps every ID clearly brighter than the black background
+    RGBColor::new(f32::from(r), f32::from(g), f32::from(b)) / 255.0 * 0.8 + 0.2
+}
+
+impl fmt::Display for Aov {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Beauty => "beauty",
+            Self::Depth => "depth",
+            Self::Normal => "normal",
+            Self::Albedo => "albedo",
+            Self::ObjectId => "object_id",
+            Self::MaterialId => "material_id",
+            Self::Uv => "uv",
+            Self::Direct => "direct",
+            Self::Indirect => "indirect",
+            Self::ShadowMask => "shadow_mask",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|aov| aov.to_string() == name).ok_or_else(|| {
+            format!(
+                "unknown AOV '{name}', use beauty, depth, normal, albedo, object_id, \
+                 material_id, uv, direct, indirect or shadow_mask"
+            )
+        })
+    }
+}
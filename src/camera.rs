@@ -1,3 +1,6 @@
+use crate::{
+    hitrecord::HitRecord, projection::Projection, ray::Ray, world::scenegraph::Scenegraph,
+};
 use glam::{IVec2, Mat4, Vec3, Vec4};
 use sdl2::keyboard::Keycode;
 
@@ -10,11 +13,28 @@ pub struct Camera {
     fov: f32,
     pub speed: f32,
     scale_factor: f32,
+    /// Radius of the thin lens, 0 is a pinhole camera where everything is sharp
+    aperture_radius: f32,
+    /// Distance along the view direction of the plane that is in focus
+    focal_distance: f32,
+    /// Keeps whatever is under the center of the screen in focus
+    is_autofocus_enabled: bool,
+    projection: Projection,
+    /// World units covered by the height of the image with the orthographic projection, `None`
+    /// frames the focal plane the same way the perspective projection does
+    orthographic_height: Option<f32>,
+    /// Angle covered by the diameter of the fisheye circle, in degrees
+    fisheye_fov: f32,
     update_look_at: bool,
+    revision: u64,
 }
 
 impl Camera {
     const WORLD_UP_VECTOR: Vec3 = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
+    const DEFAULT_FOCAL_DISTANCE: f32 = 10.0;
+    const MIN_FOCAL_DISTANCE: f32 = 0.1;
+    const DEFAULT_FISHEYE_FOV: f32 = 180.0;
+    const MAX_FISHEYE_FOV: f32 = 360.0;
 
     pub fn new(position: Vec3, fov: f32) -> Self {
         let mut camera = Self {
@@ -26,7 +46,14 @@ impl Camera {
             fov,
             speed: 15.0,
             scale_factor: (fov.to_radians() / 2.0).tan(),
+            aperture_radius: 0.0,
+            focal_distance: Self::DEFAULT_FOCAL_DISTANCE,
+            is_autofocus_enabled: false,
+            projection: Projection::Perspective,
+            orthographic_height: None,
+            fisheye_fov: Self::DEFAULT_FISHEYE_FOV,
             update_look_at: true,
+            revision: 0,
         };
         camera.calculate_look_at();
         camera
@@ -36,6 +63,7 @@ impl Camera {
     pub fn set_fov(&mut self, fov: f32) {
         self.fov = fov;
         self.scale_factor = (fov.to_radians() / 2.0).tan();
+        self.update_look_at = true;
     }
 
     #[inline]
@@ -58,6 +86,128 @@ impl Camera {
         self.scale_factor
     }
 
+    #[inline]
+    pub const fn get_aperture_radius(&self) -> f32 {
+        self.aperture_radius
+    }
+
+    #[inline]
+    pub const fn get_focal_distance(&self) -> f32 {
+        self.focal_distance
+    }
+
+    #[inline]
+    pub const fn is_autofocus_enabled(&self) -> bool {
+        self.is_autofocus_enabled
+    }
+
+    #[inline]
+    pub const fn get_projection(&self) -> Projection {
+        self.projection
+    }
+
+    #[inline]
+    pub fn get_orthographic_height(&self) -> f32 {
+        self.orthographic_height.unwrap_or(2.0 * self.scale_factor * self.focal_distance)
+    }
+
+    #[inline]
+    pub const fn get_fisheye_fov_radians(&self) -> f32 {
+        self.fisheye_fov.to_radians()
+    }
+
+    pub const fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+        self.update_look_at = true;
+    }
+
+    pub const fn set_orthographic_height(&mut self, orthographic_height: f32) {
+        self.orthographic_height = Some(orthographic_height.max(f32::EPSILON));
+        self.update_look_at = true;
+    }
+
+    pub const fn set_fisheye_fov(&mut self, fisheye_fov: f32) {
+        self.fisheye_fov = fisheye_fov.clamp(f32::EPSILON, Self::MAX_FISHEYE_FOV);
+        self.update_look_at = true;
+    }
+
+    pub fn toggle_projection(&mut self) {
+        self.set_projection(self.projection.next());
+        println!("Projection: {}", self.projection);
+    }
+
+    pub const fn set_aperture_radius(&mut self, aperture_radius: f32) {
+        self.aperture_radius = aperture_radius.max(0.0);
+        self.update_look_at = true;
+    }
+
+    pub const fn set_focal_distance(&mut self, focal_distance: f32) {
+        self.focal_distance = focal_distance.max(Self::MIN_FOCAL_DISTANCE);
+        self.update_look_at = true;
+    }
+
+    pub const fn set_autofocus(&mut self, is_autofocus_enabled: bool) {
+        self.is_autofocus_enabled = is_autofocus_enabled;
+    }
+
+    pub fn change_aperture_radius(&mut self, value: f32) {
+        self.set_aperture_radius(self.aperture_radius + value);
+        println!("aperture radius: {}", self.aperture_radius);
+    }
+
+    /// Moves the plane in focus by hand, which turns autofocus off.
+    pub fn change_focal_distance(&mut self, value: f32) {
+        self.is_autofocus_enabled = false;
+        self.set_focal_distance(self.focal_distance + value);
+        println!("focal distance: {}", self.focal_distance);
+    }
+
+    pub fn toggle_autofocus(&mut self) {
+        self.is_autofocus_enabled = !self.is_autofocus_enabled;
+        println!("autofocus: {}", if self.is_autofocus_enabled { "on" } else { "off" });
+    }
+
+    /// Focuses on the object under the center of the screen when autofocus is enabled. Nothing
+    /// changes when the center shows the background.
+    pub fn update_autofocus(&mut self, scenegraph: &Scenegraph<'_>) {
+        if !self.is_autofocus_enabled {
+            return;
+        }
+
+        let mut ray = Ray::new(self.position, -self.get_forward_vector());
+        let mut hit_record = HitRecord::default();
+        if !scenegraph.hit(&mut ray, &mut hit_record, false) {
+            return;
+        }
+
+        // Small changes would restart the path tracer every frame for nothing
+        let focal_distance = hit_record.t.max(Self::MIN_FOCAL_DISTANCE);
+        if (focal_distance - self.focal_distance).abs() > 1e-3 * self.focal_distance {
+            self.set_focal_distance(focal_distance);
+            self.update_look_at();
+        }
+    }
+
+    /// Increases every time the view changes, so renderers can tell when their samples are stale.
+    #[inline]
+    pub const fn get_revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub const fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+        self.update_look_at = true;
+    }
+
+    /// Points the camera at `target`, keeping its position.
+    pub fn set_target(&mut self, target: Vec3) {
+        let forward = self.position - target;
+        if forward.length_squared() > 0.0 {
+            self.forward_vector = forward.normalize().extend(0.0);
+            self.update_look_at = true;
+        }
+    }
+
     pub fn update_look_at(&mut self) {
         if self.update_look_at {
             self.calculate_look_at();
@@ -114,6 +264,9 @@ impl Camera {
     }
 
     fn calculate_look_at(&mut self) {
+        self.update_look_at = false;
+        self.revision += 1;
+
         self.right_vector =
             Self::WORLD_UP_VECTOR.cross(self.forward_vector.truncate()).normalize().extend(0.0);
 
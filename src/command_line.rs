@@ -0,0 +1,144 @@
+use crate::{
+    aov::Aov,
+    headless::HeadlessOptions,
+    image_writer::{get_extension, SUPPORTED_EXTENSIONS},
+    math::{pixel_filter::PixelFilter, tone_mapping::ToneMapper},
+    projection::Projection,
+};
+use std::path::PathBuf;
+
+type Error = Box<dyn std::error::Error>;
+
+pub const USAGE: &str = "Usage: rust_raytracer [--scene-file <path>] [--headless [options]]\n\n\
+                         Without --headless the interactive SDL2 window is opened.\n\n\
+                         Options:\n  \
+                           --scene-file <path>  .toml scene to load instead of the built-in scenes\n\n\
+                         Headless options:\n  \
+                           --scene <index>      scene to render, starting at 0 (default 0)\n  \
+                           --width <pixels>     image width (default 800)\n  \
+                           --height <pixels>    image height (default 600)\n  \
+                           --output <path>      .png or .ppm image, or .exr or .hdr for the raw radiance (default render.png)\n  \
+                           --samples <count>    path trace with this many samples per pixel\n  \
+                           --aa <count>         anti-aliasing samples per pixel along each axis (default 1)\n  \
+                           --filter <name>      anti-aliasing filter: box, tent, gaussian or mitchell (default box)\n  \
+                           --projection <name>  camera projection: perspective, orthographic, equirectangular, fisheye or cube_<positive|negative>_<x|y|z>\n  \
+                           --exposure <stops>   brighten or darken the image, every stop doubles the brightness (default 0)\n  \
+                           --tone-map <name>    tone mapper: normalize, reinhard, aces, uncharted2 or agx (default aces)\n  \
+                           --time <seconds>     point in the animation of the scene to render (default 0)\n  \
+                           --frames <count>     render a sequence of numbered images, starting at --time\n  \
+                           --fps <rate>         frames per second of the sequence (default 24)\n  \
+                           --y4m <path>         also write the frames to an uncompressed .y4m video\n  \
+                           --aov <names>        comma separated passes to write as well: depth, normal, albedo, object_id, material_id, uv, direct, indirect, shadow_mask or all";
+
+pub struct CommandLineOptions {
+    /// `None` when the interactive window should be opened
+    pub headless: Option<HeadlessOptions>,
+    pub scene_file: Option<PathBuf>,
+}
+
+impl CommandLineOptions {
+    /// Parses the command line arguments (without the program name).
+    pub fn from_args(args: &[String]) -> Result<Self, Error> {
+        let is_headless = args.iter().any(|arg| arg == "--headless");
+        let mut headless_options = HeadlessOptions::default();
+        let mut scene_file = None;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut next_value =
+                || args.next().ok_or_else(|| Error::from(format!("missing value for '{arg}'")));
+
+            match arg.as_str() {
+                "--headless" => {}
+                "--scene-file" => scene_file = Some(PathBuf::from(next_value()?)),
+                "--scene" | "--width" | "--height" | "--output" | "--samples" | "--aa"
+                | "--filter" | "--projection" | "--exposure" | "--tone-map" | "--time"
+                | "--frames" | "--fps" | "--y4m" | "--aov"
+                    if !is_headless =>
+                {
+                    return Err(format!("'{arg}' can only be used with --headless").into());
+                }
+                "--scene" => headless_options.scene_index = parse_value(arg, next_value()?)?,
+                "--width" => headless_options.width = parse_value(arg, next_value()?)?,
+                "--height" => headless_options.height = parse_value(arg, next_value()?)?,
+                "--output" => headless_options.output_path = PathBuf::from(next_value()?),
+                "--samples" => headless_options.samples = Some(parse_value(arg, next_value()?)?),
+                "--aa" => {
+                    headless_options.pixel_samples_per_axis = parse_value(arg, next_value()?)?;
+                }
+                "--filter" => {
+                    headless_options.pixel_filter = next_value()?.parse::<PixelFilter>()?;
+                }
+                "--projection" => {
+                    headless_options.projection = Some(next_value()?.parse::<Projection>()?);
+                }
+                "--exposure" => headless_options.exposure = parse_value(arg, next_value()?)?,
+                "--tone-map" => {
+                    headless_options.tone_mapper = next_value()?.parse::<ToneMapper>()?;
+                }
+                "--time" => headless_options.time = parse_value(arg, next_value()?)?,
+                "--frames" => {
+                    headless_options.frame_count = Some(parse_value(arg, next_value()?)?);
+                }
+                "--fps" => headless_options.frames_per_second = parse_value(arg, next_value()?)?,
+                "--y4m" => headless_options.y4m_path = Some(PathBuf::from(next_value()?)),
+                "--aov" => headless_options.aovs = parse_aovs(next_value()?)?,
+                _ => return Err(format!("unknown argument '{arg}'").into()),
+            }
+        }
+
+        if !is_headless {
+            return Ok(Self { headless: None, scene_file });
+        }
+
+        if headless_options.width == 0 || headless_options.height == 0 {
+            return Err("width and height must be greater than 0".into());
+        }
+
+        if headless_options.samples == Some(0) {
+            return Err("samples must be greater than 0".into());
+        }
+
+        if headless_options.frame_count == Some(0) {
+            return Err("frames must be greater than 0".into());
+        }
+
+        if headless_options.frames_per_second == 0 {
+            return Err("fps must be greater than 0".into());
+        }
+
+        if let Some(y4m_path) = &headless_options.y4m_path {
+            if get_extension(y4m_path).as_deref() != Some("y4m") {
+                return Err(format!("{} is not a .y4m file", y4m_path.display()).into());
+            }
+        }
+
+        if headless_options.pixel_samples_per_axis == 0 {
+            return Err("aa must be greater than 0".into());
+        }
+
+        if !get_extension(&headless_options.output_path)
+            .is_some_and(|extension| SUPPORTED_EXTENSIONS.contains(&extension.as_str()))
+        {
+            return Err(format!(
+                "unsupported output format for {}, use .png, .ppm, .exr or .hdr",
+                headless_options.output_path.display()
+            )
+            .into());
+        }
+
+        Ok(Self { headless: Some(headless_options), scene_file })
+    }
+}
+
+fn parse_aovs(names: &str) -> Result<Vec<Aov>, Error> {
+    if names == "all" {
+        return Ok(Aov::ALL.into_iter().filter(|aov| *aov != Aov::Beauty).collect());
+    }
+
+    names.split(',').map(|name| name.trim().parse::<Aov>().map_err(Error::from)).collect()
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, Error> {
+    value.parse().map_err(|_| format!("invalid value '{value}' for '{arg}'").into())
+}
@@ -0,0 +1,231 @@
+use crate::{
+    aov::Aov,
+    camera::Camera,
+    image_writer::{is_hdr_path, write_hdr_image, write_image},
+    lights::{light_manager::LightManager, LightEnum},
+    materials::material_manager::MaterialManager,
+    math::{pixel_filter::PixelFilter, tone_mapping::ToneMapper},
+    projection::Projection,
+    renderer::{Integrator, Renderer},
+    video_writer::Y4mWriter,
+    world::{
+        scene_file::SceneDescription,
+        scene_manager::SceneManager,
+        world_creation::{create_lights, create_materials, create_scenes},
+    },
+};
+use glam::Vec3;
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Settings for rendering a single image to disk without opening a window.
+pub struct HeadlessOptions {
+    pub scene_index: usize,
+    pub width: u32,
+    pub height: u32,
+    pub output_path: PathBuf,
+    /// Path traced samples per pixel, `None` renders a single Whitted frame
+    pub samples: Option<u32>,
+    /// Anti-aliasing samples per pixel along each axis
+    pub pixel_samples_per_axis: u32,
+    pub pixel_filter: PixelFilter,
+    /// Replaces the projection of the camera
+    pub projection: Option<Projection>,
+    /// Exposure in stops
+    pub exposure: f32,
+    pub tone_mapper: ToneMapper,
+    /// Seconds into the animation of the scene
+    pub time: f32,
+    /// Renders a sequence of numbered images, `<name>_0000.<extension>` and on, instead of a
+    /// single image
+    pub frame_count: Option<u32>,
+    /// The animation moves forward by one over this many seconds between frames
+    pub frames_per_second: u32,
+    /// Also writes the frames to an uncompressed video stream
+    pub y4m_path: Option<PathBuf>,
+    /// Extra passes, each written next to the output as `<name>_<aov>.<extension>`
+    pub aovs: Vec<Aov>,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            scene_index: 0,
+            width: 800,
+            height: 600,
+            output_path: PathBuf::from("render.png"),
+            samples: None,
+            pixel_samples_per_axis: 1,
+            pixel_filter: PixelFilter::Box,
+            projection: None,
+            exposure: 0.0,
+            tone_mapper: ToneMapper::Aces,
+            time: 0.0,
+            frame_count: None,
+            frames_per_second: 24,
+            y4m_path: None,
+            aovs: Vec::new(),
+        }
+    }
+}
+
+/// Renders one frame of the chosen scene, or a sequence of frames stepping its animation forward,
+/// and writes them to disk. Uses the scene file when one is given, otherwise the built-in scenes
+/// with the default camera.
+pub fn render_to_file(options: &HeadlessOptions, scene_file: Option<&Path>) -> Result<(), Error> {
+    let scene_description = scene_file.map(SceneDescription::from_file).transpose()?;
+
+    let mut material_manager: MaterialManager<'_> = MaterialManager::new();
+    let mut light_manager: LightManager<LightEnum> = LightManager::new();
+    let mut scene_manager: SceneManager<'_> = SceneManager::new();
+
+    let mut camera = if let Some(scene_description) = &scene_description {
+        scene_description.create_materials(&mut material_manager)?;
+        scene_description.create_lights(&mut light_manager)?;
+        scene_description.create_scene(&mut scene_manager, &material_manager)?;
+        scene_description.create_camera()
+    } else {
+        create_materials(&mut material_manager);
+        create_lights(&mut light_manager);
+        create_scenes(&mut scene_manager, &material_manager);
+        Camera::new(Vec3::new(0.0, 2.0, 15.0), 45.0)
+    };
+
+    if options.scene_index >= scene_manager.num_scenes() {
+        return Err(format!(
+            "scene {} does not exist, there are {} scenes",
+            options.scene_index,
+            scene_manager.num_scenes()
+        )
+        .into());
+    }
+    scene_manager.set_current_scene(options.scene_index);
+    let animation = scene_manager.get_current_scene_mut().get_animation_mut();
+    animation.set_time(options.time);
+    // Sequences play the animation even when the scene doesn't start it on its own
+    if options.frame_count.is_some() {
+        animation.set_playing(true);
+    }
+    scene_manager.update(0.0, &mut camera, &mut light_manager);
+    if let Some(projection) = options.projection {
+        camera.set_projection(projection);
+    }
+    check_image_size(camera.get_projection(), options.width, options.height)?;
+
+    let mut render_system = Renderer::new(options.width, options.height);
+    render_system.pixel_samples_per_axis = options.pixel_samples_per_axis;
+    render_system.pixel_filter = options.pixel_filter;
+    render_system.exposure = options.exposure;
+    render_system.tone_mapper = options.tone_mapper;
+    if options.samples.is_some() {
+        render_system.integrator = Integrator::PathTracer;
+    }
+
+    let mut video_writer = options
+        .y4m_path
+        .as_deref()
+        .map(|path| {
+            Y4mWriter::create(path, options.width, options.height, options.frames_per_second)
+        })
+        .transpose()?;
+
+    #[allow(clippy::cast_precision_loss)]
+    let timestep = 1.0 / options.frames_per_second as f32;
+    let frame_count = options.frame_count.unwrap_or(1);
+    for frame in 0..frame_count {
+        if frame > 0 {
+            scene_manager.update(timestep, &mut camera, &mut light_manager);
+        }
+        camera.update_autofocus(scene_manager.get_current_scene());
+        camera.update_look_at();
+
+        let output_path = if options.frame_count.is_some() {
+            get_frame_path(&options.output_path, frame, frame_count)
+        } else {
+            options.output_path.clone()
+        };
+
+        let aovs = std::iter::once(Aov::Beauty).chain(options.aovs.iter().copied());
+        for aov in aovs {
+            let aov_path = if aov == Aov::Beauty {
+                output_path.clone()
+            } else {
+                get_aov_path(&output_path, aov)
+            };
+            render_system.set_aov(aov);
+
+            let start_time = Instant::now();
+            // Every path traced frame adds one sample per pixel to the image
+            for _ in 0..options.samples.unwrap_or(1) {
+                render_system.render(scene_manager.get_current_scene(), &camera, &light_manager);
+            }
+            println!(
+                "Rendered the {aov} pass of scene {} at {}x{} in {:.2?}",
+                options.scene_index,
+                options.width,
+                options.height,
+                start_time.elapsed()
+            );
+
+            write_framebuffer(&render_system, options, &aov_path)?;
+            println!("Saved {}", aov_path.display());
+
+            if let (Aov::Beauty, Some(video_writer)) = (aov, video_writer.as_mut()) {
+                let mut pixel_data = vec![0; (options.width * options.height) as usize];
+                render_system.write_display_pixels(&mut pixel_data);
+                video_writer.write_frame(&pixel_data)?;
+            }
+        }
+    }
+
+    if let (Some(video_writer), Some(y4m_path)) = (video_writer, &options.y4m_path) {
+        video_writer.finish()?;
+        println!("Saved {frame_count} frames to {}", y4m_path.display());
+    }
+
+    Ok(())
+}
+
+/// A cube face only covers 90° on both axes without distortion when the image is square.
+fn check_image_size(projection: Projection, width: u32, height: u32) -> Result<(), Error> {
+    if matches!(projection, Projection::CubeFace(_)) && width != height {
+        return Err(
+            format!("cube face projections need a square image, got {width}x{height}").into()
+        );
+    }
+    Ok(())
+}
+
+/// HDR formats keep the values as they are, exposure and tone mapping only apply to 8-bit images.
+fn write_framebuffer(
+    render_system: &Renderer,
+    options: &HeadlessOptions,
+    output_path: &Path,
+) -> Result<(), Error> {
+    if is_hdr_path(output_path) {
+        write_hdr_image(output_path, options.width, options.height, render_system.get_framebuffer())
+    } else {
+        let mut pixel_data = vec![0; (options.width * options.height) as usize];
+        render_system.write_display_pixels(&mut pixel_data);
+        write_image(output_path, options.width, options.height, &pixel_data)
+    }
+}
+
+/// `render.png` becomes `render_0007.png` for the eighth frame, with at least four digits.
+fn get_frame_path(output_path: &Path, frame: u32, frame_count: u32) -> PathBuf {
+    let digits = (frame_count - 1).to_string().len().max(4);
+    let stem = output_path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = output_path.extension().unwrap_or_default().to_string_lossy();
+    output_path.with_file_name(format!("{stem}_{frame:0digits$}.{extension}"))
+}
+
+/// `render.png` becomes `render_depth.png` for the depth pass.
+fn get_aov_path(output_path: &Path, aov: Aov) -> PathBuf {
+    let stem = output_path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = output_path.extension().unwrap_or_default().to_string_lossy();
+    output_path.with_file_name(format!("{stem}_{aov}.{extension}"))
+}
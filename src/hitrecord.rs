@@ -1,5 +1,6 @@
 use crate::materials::MaterialEnum;
-use glam::Vec3;
+use crate::math::sampling::orthonormal_basis;
+use glam::{Vec2, Vec3};
 
 #[derive(Clone)]
 pub struct HitRecord<'mm> {
@@ -7,16 +8,56 @@ pub struct HitRecord<'mm> {
     pub normal: Vec3,
     pub material: Option<&'mm MaterialEnum>,
     pub t: f32,
+    /// Texture coordinates of the hitpoint
+    pub uv: Vec2,
+    /// Unit vector along the surface in the direction u increases in
+    pub tangent: Vec3,
+    /// Unit vector along the surface in the direction v increases in, perpendicular to the tangent
+    pub bitangent: Vec3,
+    /// Starts at 1 for the first object of the scenegraph, 0 until an object was hit
+    pub object_id: u32,
 }
 
 impl<'mm> HitRecord<'mm> {
     pub const fn new(hitpoint: Vec3, normal: Vec3, material: &'mm MaterialEnum, t: f32) -> Self {
-        Self { hitpoint, normal, material: Some(material), t }
+        Self {
+            hitpoint,
+            normal,
+            material: Some(material),
+            t,
+            uv: Vec2::ZERO,
+            tangent: Vec3::ZERO,
+            bitangent: Vec3::ZERO,
+            object_id: 0,
+        }
+    }
+
+    /// Builds an orthonormal tangent frame around the normal, which has to be set first, from the
+    /// derivatives of the hitpoint along u and v. Only the directions of the derivatives matter.
+    pub fn set_tangent_frame(&mut self, tangent_direction: Vec3, bitangent_direction: Vec3) {
+        let tangent = (tangent_direction - self.normal * self.normal.dot(tangent_direction))
+            .normalize_or_zero();
+        self.tangent =
+            if tangent == Vec3::ZERO { orthonormal_basis(self.normal).0 } else { tangent };
+
+        // Mirrored texture coordinates flip the bitangent
+        let bitangent = self.normal.cross(self.tangent);
+        self.bitangent =
+            if bitangent.dot(bitangent_direction) < 0.0 { -bitangent } else { bitangent };
     }
 }
 
 impl<'mm> Default for HitRecord<'mm> {
     fn default() -> Self {
-        Self { hitpoint: Vec3::ZERO, normal: Vec3::ZERO, material: None, t: 0.0 }
+        Self {
+            hitpoint: Vec3::ZERO,
+            normal: Vec3::ZERO,
+            material: None,
+            t: 0.0,
+            uv: Vec2::ZERO,
+            tangent: Vec3::ZERO,
+            bitangent: Vec3::ZERO,
+            object_id: 0,
+        }
     }
 }
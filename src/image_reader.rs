@@ -0,0 +1,259 @@
+use glam::Vec3;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+type Error = Box<dyn std::error::Error>;
+pub type RGBColor = Vec3;
+
+/// Largest `.hdr` image accepted, the header is untrusted and could ask for any size
+const MAX_HDR_SIDE: usize = 1 << 16;
+const MAX_HDR_PIXEL_COUNT: usize = 1 << 28;
+
+/// Floating point image, rows are stored from top to bottom.
+#[derive(Clone, PartialEq)]
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<RGBColor>,
+}
+
+impl HdrImage {
+    pub fn get_pixel(&self, x: u32, y: u32) -> RGBColor {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+/// Reads a PNG, JPEG or Radiance `.hdr` image depending on the extension of `path`. PNG and JPEG
+/// values are mapped to [0, 1] but stay in the color space they were stored in.
+pub fn read_image(path: &Path) -> Result<HdrImage, Error> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let read_ldr = match extension.as_str() {
+        "hdr" => return read_hdr(path),
+        "png" => read_png_from,
+        "jpg" | "jpeg" => read_jpeg_from,
+        _ => {
+            return Err(format!(
+                "{}: unsupported image format, use .png, .jpg, .jpeg or .hdr",
+                path.display()
+            )
+            .into())
+        }
+    };
+
+    File::open(path)
+        .map_err(Error::from)
+        .and_then(|file| read_ldr(BufReader::new(file)))
+        .map_err(|error| format!("{}: {error}", path.display()).into())
+}
+
+/// Reads a Radiance RGBE (`.hdr`) image, both flat and run length encoded scanlines are supported.
+pub fn read_hdr(path: &Path) -> Result<HdrImage, Error> {
+    File::open(path)
+        .map_err(Error::from)
+        .and_then(|file| read_hdr_from(&mut BufReader::new(file)))
+        .map_err(|error| format!("{}: {error}", path.display()).into())
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn read_hdr_from<R: BufRead>(reader: &mut R) -> Result<HdrImage, Error> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !line.starts_with("#?") {
+        return Err("not a Radiance HDR file, the #? signature is missing".into());
+    }
+
+    // Header variables end with an empty line
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err("unexpected end of file in the header".into());
+        }
+        let variable = line.trim();
+        if variable.is_empty() {
+            break;
+        }
+        if let Some(format) = variable.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(format!("unsupported pixel format '{format}'").into());
+            }
+        }
+    }
+
+    line.clear();
+    reader.read_line(&mut line)?;
+    let resolution: Vec<&str> = line.split_whitespace().collect();
+    let (is_flipped, height, width) = match resolution.as_slice() {
+        ["-Y", height, "+X", width] => (false, height, width),
+        ["+Y", height, "+X", width] => (true, height, width),
+        _ => return Err(format!("unsupported resolution line '{}'", line.trim()).into()),
+    };
+    let height: u32 = height.parse().map_err(|_| format!("invalid image height '{height}'"))?;
+    let width: u32 = width.parse().map_err(|_| format!("invalid image width '{width}'"))?;
+    if width == 0 || height == 0 {
+        return Err("image has no pixels".into());
+    }
+
+    let row_length = usize::try_from(width)?;
+    let row_count = usize::try_from(height)?;
+    let too_large = || format!("image size {width}x{height} is too large");
+    let pixel_count = row_length.checked_mul(row_count).ok_or_else(too_large)?;
+    if row_length > MAX_HDR_SIDE || row_count > MAX_HDR_SIDE || pixel_count > MAX_HDR_PIXEL_COUNT {
+        return Err(too_large().into());
+    }
+
+    // Grows with the scanlines actually read, so a truncated file fails before allocating the
+    // size its header claims
+    let mut pixels = Vec::new();
+    let mut scanline = vec![[0u8; 4]; row_length];
+    for _ in 0..row_count {
+        read_scanline(reader, &mut scanline)?;
+        pixels.extend(scanline.iter().map(|rgbe| rgbe_to_color(*rgbe)));
+    }
+
+    if is_flipped {
+        let rows: Vec<&[RGBColor]> = pixels.chunks_exact(row_length).rev().collect();
+        pixels = rows.concat();
+    }
+
+    Ok(HdrImage { width, height, pixels })
+}
+
+fn read_png_from(reader: BufReader<File>) -> Result<HdrImage, Error> {
+    let mut decoder = png::Decoder::new(reader);
+    // Palettes are expanded and 16-bit channels reduced so every image ends up as 8-bit gray or RGB
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut png_reader = decoder.read_info()?;
+    let mut buffer = vec![0; png_reader.output_buffer_size()];
+    let info = png_reader.next_frame(&mut buffer)?;
+    let bytes = &buffer[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Grayscale => bytes.iter().map(|gray| gray_to_color(*gray)).collect(),
+        png::ColorType::GrayscaleAlpha => {
+            bytes.chunks_exact(2).map(|gray_alpha| gray_to_color(gray_alpha[0])).collect()
+        }
+        png::ColorType::Rgb => bytes.chunks_exact(3).map(rgb_to_color).collect(),
+        png::ColorType::Rgba => bytes.chunks_exact(4).map(rgb_to_color).collect(),
+        png::ColorType::Indexed => return Err("indexed colors were not expanded".into()),
+    };
+
+    Ok(HdrImage { width: info.width, height: info.height, pixels })
+}
+
+fn read_jpeg_from(reader: BufReader<File>) -> Result<HdrImage, Error> {
+    let mut decoder = jpeg_decoder::Decoder::new(reader);
+    let bytes = decoder.decode()?;
+    let info = decoder.info().ok_or("JPEG image has no frame header")?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => bytes.iter().map(|gray| gray_to_color(*gray)).collect(),
+        // Big endian, the high byte is precise enough for textures
+        jpeg_decoder::PixelFormat::L16 => {
+            bytes.chunks_exact(2).map(|gray| gray_to_color(gray[0])).collect()
+        }
+        jpeg_decoder::PixelFormat::RGB24 => bytes.chunks_exact(3).map(rgb_to_color).collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => {
+            return Err("CMYK JPEG images are not supported".into())
+        }
+    };
+
+    Ok(HdrImage { width: u32::from(info.width), height: u32::from(info.height), pixels })
+}
+
+fn gray_to_color(gray: u8) -> RGBColor {
+    RGBColor::splat(f32::from(gray) / 255.0)
+}
+
+fn rgb_to_color(rgb: &[u8]) -> RGBColor {
+    RGBColor::new(f32::from(rgb[0]), f32::from(rgb[1]), f32::from(rgb[2])) / 255.0
+}
+
+fn read_scanline<R: Read>(reader: &mut R, scanline: &mut [[u8; 4]]) -> Result<(), Error> {
+    let width = scanline.len();
+    let mut first = [0u8; 4];
+    reader.read_exact(&mut first)?;
+
+    // Newer files store every channel of a scanline separately, run length encoded
+    let is_run_length_encoded = (8..0x8000).contains(&width)
+        && first[0] == 2
+        && first[1] == 2
+        && usize::from(first[2]) << 8 | usize::from(first[3]) == width;
+    if !is_run_length_encoded {
+        scanline[0] = first;
+        return read_flat_scanline(reader, scanline);
+    }
+
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let mut count = [0u8; 1];
+            reader.read_exact(&mut count)?;
+            let count = usize::from(count[0]);
+
+            if count > 128 {
+                let run_length = count - 128;
+                if x + run_length > width {
+                    return Err("run length encoded scanline is too long".into());
+                }
+                let mut value = [0u8; 1];
+                reader.read_exact(&mut value)?;
+                scanline[x..x + run_length].iter_mut().for_each(|rgbe| rgbe[channel] = value[0]);
+                x += run_length;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err("run length encoded scanline has an invalid run".into());
+                }
+                let mut values = vec![0u8; count];
+                reader.read_exact(&mut values)?;
+                for (rgbe, value) in scanline[x..x + count].iter_mut().zip(values) {
+                    rgbe[channel] = value;
+                }
+                x += count;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the remaining pixels of a scanline, `scanline[0]` is already filled in. Old style runs
+/// are stored as pixels of (1, 1, 1, count) that repeat the previous pixel.
+fn read_flat_scanline<R: Read>(reader: &mut R, scanline: &mut [[u8; 4]]) -> Result<(), Error> {
+    let mut x = 1;
+    while x < scanline.len() {
+        let mut rgbe = [0u8; 4];
+        reader.read_exact(&mut rgbe)?;
+
+        if rgbe[..3] == [1, 1, 1] {
+            let run_length = usize::from(rgbe[3]).min(scanline.len() - x);
+            if run_length == 0 {
+                return Err("scanline has an empty run".into());
+            }
+            let previous = scanline[x - 1];
+            scanline[x..x + run_length].fill(previous);
+            x += run_length;
+        } else {
+            scanline[x] = rgbe;
+            x += 1;
+        }
+    }
+
+    Ok(())
+}
+
+fn rgbe_to_color([r, g, b, exponent]: [u8; 4]) -> RGBColor {
+    if exponent == 0 {
+        return RGBColor::ZERO;
+    }
+
+    let scale = 2.0f32.powi(i32::from(exponent) - (128 + 8));
+    RGBColor::new(f32::from(r) + 0.5, f32::from(g) + 0.5, f32::from(b) + 0.5) * scale
+}
@@ -0,0 +1,244 @@
+use glam::Vec3;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+type Error = Box<dyn std::error::Error>;
+pub type RGBColor = Vec3;
+
+pub const SUPPORTED_EXTENSIONS: [&str; 4] = ["png", "ppm", "exr", "hdr"];
+/// Formats that store the linear radiance of the framebuffer instead of display pixels
+pub const HDR_EXTENSIONS: [&str; 2] = ["exr", "hdr"];
+
+/// Writes `0x00RRGGBB` pixels, as produced by the renderer, to an 8-bit image file.
+/// The format is picked from the file extension: `.png` or `.ppm`.
+pub fn write_image(path: &Path, width: u32, height: u32, pixel_data: &[u32]) -> Result<(), Error> {
+    if pixel_data.len() != (width * height) as usize {
+        return Err(format!(
+            "pixel buffer holds {} pixels, expected {width}x{height}",
+            pixel_data.len()
+        )
+        .into());
+    }
+
+    match get_extension(path).as_deref() {
+        Some("png") => write_png(path, width, height, pixel_data),
+        Some("ppm") => write_ppm(path, width, height, pixel_data),
+        _ => {
+            Err(format!("unsupported image format for {}, use .png or .ppm", path.display()).into())
+        }
+    }
+}
+
+/// Writes linear floating point colors to a high dynamic range image file for compositing.
+/// The format is picked from the file extension: `.exr` (`OpenEXR`) or `.hdr` (Radiance).
+pub fn write_hdr_image(
+    path: &Path,
+    width: u32,
+    height: u32,
+    colors: &[RGBColor],
+) -> Result<(), Error> {
+    if colors.len() != (width * height) as usize {
+        return Err(format!(
+            "framebuffer holds {} pixels, expected {width}x{height}",
+            colors.len()
+        )
+        .into());
+    }
+
+    match get_extension(path).as_deref() {
+        Some("exr") => write_exr(path, width, height, colors),
+        Some("hdr") => write_hdr(path, width, height, colors),
+        _ => {
+            Err(format!("unsupported HDR image format for {}, use .exr or .hdr", path.display())
+                .into())
+        }
+    }
+}
+
+pub fn is_hdr_path(path: &Path) -> bool {
+    get_extension(path).is_some_and(|extension| HDR_EXTENSIONS.contains(&extension.as_str()))
+}
+
+pub fn get_extension(path: &Path) -> Option<String> {
+    path.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase)
+}
+
+fn to_rgb_bytes(pixel_data: &[u32]) -> Vec<u8> {
+    pixel_data
+        .iter()
+        .flat_map(|pixel| {
+            let [b, g, r, _] = pixel.to_le_bytes();
+            [r, g, b]
+        })
+        .collect()
+}
+
+fn write_png(path: &Path, width: u32, height: u32, pixel_data: &[u32]) -> Result<(), Error> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&to_rgb_bytes(pixel_data))?;
+    writer.finish()?;
+    Ok(())
+}
+
+fn write_ppm(path: &Path, width: u32, height: u32, pixel_data: &[u32]) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "P6\n{width} {height}\n255\n")?;
+    writer.write_all(&to_rgb_bytes(pixel_data))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Uncompressed scanline `OpenEXR` with 32-bit float B, G and R channels.
+#[allow(clippy::cast_possible_wrap)]
+#[allow(clippy::cast_possible_truncation)]
+fn write_exr(path: &Path, width: u32, height: u32, colors: &[RGBColor]) -> Result<(), Error> {
+    const MAGIC_NUMBER: u32 = 20_000_630;
+    const VERSION: u32 = 2;
+    const FLOAT_PIXEL_TYPE: i32 = 2;
+    // Channels have to be listed in alphabetical order
+    const CHANNELS: [&str; 3] = ["B", "G", "R"];
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&MAGIC_NUMBER.to_le_bytes());
+    header.extend_from_slice(&VERSION.to_le_bytes());
+
+    let mut channel_list = Vec::new();
+    for channel in CHANNELS {
+        channel_list.extend_from_slice(channel.as_bytes());
+        channel_list.push(0);
+        channel_list.extend_from_slice(&FLOAT_PIXEL_TYPE.to_le_bytes());
+        // Perceptually linear flag and three reserved bytes
+        channel_list.extend_from_slice(&[0; 4]);
+        // Sampled at every pixel along x and y
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channel_list.push(0);
+
+    let window: Vec<u8> = [0, 0, width as i32 - 1, height as i32 - 1]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    write_exr_attribute(&mut header, "channels", "chlist", &channel_list);
+    write_exr_attribute(&mut header, "compression", "compression", &[0]);
+    write_exr_attribute(&mut header, "dataWindow", "box2i", &window);
+    write_exr_attribute(&mut header, "displayWindow", "box2i", &window);
+    write_exr_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    write_exr_attribute(&mut header, "pixelAspectRatio", "float", &1.0f32.to_le_bytes());
+    write_exr_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    write_exr_attribute(&mut header, "screenWindowWidth", "float", &1.0f32.to_le_bytes());
+    header.push(0);
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&header)?;
+
+    // Every scanline is its own block, the offset table points at each of them
+    let scanline_size = width as usize * CHANNELS.len() * std::mem::size_of::<f32>();
+    let block_size = 2 * std::mem::size_of::<i32>() + scanline_size;
+    let first_block = header.len() + height as usize * std::mem::size_of::<u64>();
+    for y in 0..height as usize {
+        writer.write_all(&((first_block + y * block_size) as u64).to_le_bytes())?;
+    }
+
+    for (y, row) in colors.chunks_exact(width as usize).enumerate() {
+        writer.write_all(&(y as i32).to_le_bytes())?;
+        writer.write_all(&(scanline_size as i32).to_le_bytes())?;
+        for channel in [2, 1, 0] {
+            for color in row {
+                writer.write_all(&color[channel].to_le_bytes())?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_exr_attribute(header: &mut Vec<u8>, name: &str, attribute_type: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(attribute_type.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&u32::try_from(value.len()).unwrap_or(u32::MAX).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+/// Radiance RGBE with run length encoded scanlines, as read by `image_reader::read_hdr`.
+fn write_hdr(path: &Path, width: u32, height: u32, colors: &[RGBColor]) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {height} +X {width}\n")?;
+
+    for row in colors.chunks_exact(width as usize) {
+        let rgbe: Vec<[u8; 4]> = row.iter().map(|color| color_to_rgbe(*color)).collect();
+        // Run length encoding is only defined for these widths
+        if !(8..0x8000).contains(&width) {
+            writer.write_all(&rgbe.concat())?;
+            continue;
+        }
+
+        let [width_high, width_low] = u16::try_from(width)?.to_be_bytes();
+        writer.write_all(&[2, 2, width_high, width_low])?;
+        for channel in 0..4 {
+            let values: Vec<u8> = rgbe.iter().map(|pixel| pixel[channel]).collect();
+            write_run_length_encoded(&mut writer, &values)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Runs of at least three equal values become (128 + length, value), everything else is
+/// stored as (length, values...). Both kinds are at most 127 values long.
+fn write_run_length_encoded<W: Write>(writer: &mut W, values: &[u8]) -> Result<(), Error> {
+    const MAX_LENGTH: usize = 127;
+    const MIN_RUN_LENGTH: usize = 3;
+
+    let get_run_length = |start: usize| {
+        values[start..].iter().take(MAX_LENGTH).take_while(|value| **value == values[start]).count()
+    };
+
+    let mut x = 0;
+    while x < values.len() {
+        let run_length = get_run_length(x);
+        if run_length >= MIN_RUN_LENGTH {
+            writer.write_all(&[128 + u8::try_from(run_length)?, values[x]])?;
+            x += run_length;
+            continue;
+        }
+
+        // Literal values up to the start of the next run
+        let start = x;
+        while x < values.len() && x - start < MAX_LENGTH && get_run_length(x) < MIN_RUN_LENGTH {
+            x += 1;
+        }
+        writer.write_all(&[u8::try_from(x - start)?])?;
+        writer.write_all(&values[start..x])?;
+    }
+
+    Ok(())
+}
+
+/// Shared exponent encoding, the inverse of `image_reader::rgbe_to_color`.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn color_to_rgbe(color: RGBColor) -> [u8; 4] {
+    let color = color.max(RGBColor::ZERO);
+    let max = color.max_element();
+    if !max.is_finite() || max < 1e-32 {
+        return [0; 4];
+    }
+
+    // max = mantissa * 2^exponent with the mantissa in [0.5, 1)
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2.0f32.powi(exponent);
+    let [r, g, b] = (color * scale).to_array().map(|value| value.min(255.0) as u8);
+    [r, g, b, (exponent + 128).clamp(0, 255) as u8]
+}
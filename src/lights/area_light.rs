@@ -0,0 +1,240 @@
+use super::light_properties::{Light, LightProperties, LightSample, LightType};
+use crate::math::sampling::{direction_around_axis, orthonormal_basis};
+use crate::ray::Ray;
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+pub type RGBColor = Vec3;
+
+#[derive(Clone, PartialEq)]
+pub enum AreaLightShape {
+    /// Parallelogram spanned by two edges centered on the light position, it only emits light
+    /// on the side `edge_u × edge_v` points to.
+    Rectangle {
+        edge_u: Vec3,
+        edge_v: Vec3,
+    },
+    /// Disk that only emits light on the side its normal points to.
+    Disk {
+        normal: Vec3,
+        radius: f32,
+    },
+    Sphere {
+        radius: f32,
+    },
+}
+
+/// Light with a surface, shading points sample several points on it which gives soft shadows.
+/// The intensity is spread over the surface, so from far away it lights a scene like a point light
+/// with the same intensity.
+#[derive(Clone, PartialEq)]
+pub struct AreaLight {
+    pub light_properties: LightProperties,
+    position: Vec3,
+    shape: AreaLightShape,
+    samples_per_axis: u32,
+}
+
+impl AreaLight {
+    pub fn new(
+        light_properties: LightProperties,
+        position: Vec3,
+        shape: AreaLightShape,
+        samples_per_axis: u32,
+    ) -> Self {
+        let shape = match shape {
+            AreaLightShape::Disk { normal, radius } => {
+                AreaLightShape::Disk { normal: normal.normalize(), radius }
+            }
+            shape => shape,
+        };
+        Self { light_properties, position, shape, samples_per_axis: samples_per_axis.max(1) }
+    }
+
+    /// Area of the light as seen from straight on, a sphere looks like a disk from everywhere.
+    fn get_projected_area(&self) -> f32 {
+        match self.shape {
+            AreaLightShape::Rectangle { edge_u, edge_v } => edge_u.cross(edge_v).length(),
+            AreaLightShape::Disk { radius, .. } | AreaLightShape::Sphere { radius } => {
+                PI * radius * radius
+            }
+        }
+    }
+
+    fn get_radiance(&self) -> RGBColor {
+        self.light_properties.color * self.light_properties.intensity
+            / self.get_projected_area().max(f32::EPSILON)
+    }
+
+    /// Irradiance from a point sampled uniformly on a flat emitter with the given normal and area.
+    fn sample_flat_emitter(
+        &self,
+        position: &Vec3,
+        point_on_light: Vec3,
+        normal: Vec3,
+        area: f32,
+    ) -> LightSample {
+        let to_light = point_on_light - *position;
+        let distance_sq = to_light.length_squared().max(f32::EPSILON);
+        let distance = distance_sq.sqrt();
+        let direction = to_light / distance;
+
+        let cos_light = -direction.dot(normal);
+        let bi_radiance = if cos_light > 0.0 {
+            self.get_radiance() * (cos_light * area / distance_sq)
+        } else {
+            RGBColor::ZERO
+        };
+
+        LightSample { direction, distance, bi_radiance }
+    }
+
+    /// Samples the cone of directions in which the sphere is visible, which wastes no samples on
+    /// the back of the sphere.
+    fn sample_sphere(&self, position: &Vec3, radius: f32, random: Vec2) -> LightSample {
+        let to_center = self.position - *position;
+        let center_distance_sq = to_center.length_squared();
+        let center_distance = center_distance_sq.sqrt();
+        let axis = to_center / center_distance;
+
+        if center_distance <= radius {
+            return LightSample { direction: axis, distance: 0.0, bi_radiance: RGBColor::ZERO };
+        }
+
+        let sin_max_sq = radius * radius / center_distance_sq;
+        let cos_max = (1.0 - sin_max_sq).sqrt();
+        // 1 - cos_max without the cancellation for small or distant spheres
+        let one_minus_cos_max = sin_max_sq / (1.0 + cos_max);
+
+        let cos_theta = random.x.mul_add(-one_minus_cos_max, 1.0);
+        let direction = direction_around_axis(axis, cos_theta, 2.0 * PI * random.y);
+
+        let projection = direction.dot(to_center);
+        let discriminant =
+            projection.mul_add(projection, radius.mul_add(radius, -center_distance_sq));
+        let distance = projection - discriminant.max(0.0).sqrt();
+
+        LightSample {
+            direction,
+            distance,
+            bi_radiance: self.get_radiance() * (2.0 * PI * one_minus_cos_max),
+        }
+    }
+}
+
+impl Light for AreaLight {
+    fn get_bi_radians(&self, position: &Vec3) -> RGBColor {
+        let direction_sq = position.distance_squared(self.position);
+        self.light_properties.color * (self.light_properties.intensity / direction_sq)
+    }
+
+    fn get_direction(&self, position: &Vec3) -> Vec3 {
+        (self.position - *position).normalize()
+    }
+
+    fn get_direction_magnitude(&self, position: &Vec3, direction_magnitude: &mut f32) -> Vec3 {
+        let direction = self.position - *position;
+        *direction_magnitude = direction.length();
+        direction.normalize_or_zero()
+    }
+
+    fn get_position(&self) -> &Vec3 {
+        &self.position
+    }
+
+    fn get_position_mut(&mut self) -> &mut Vec3 {
+        &mut self.position
+    }
+
+    fn get_color_mut(&mut self) -> &mut RGBColor {
+        &mut self.light_properties.color
+    }
+
+    fn get_intensity_mut(&mut self) -> &mut f32 {
+        &mut self.light_properties.intensity
+    }
+
+    fn toggle_light(&mut self) {
+        self.light_properties.toggle();
+    }
+
+    fn get_type_light(&self) -> LightType {
+        self.light_properties.light_type.clone()
+    }
+
+    fn is_light_enabled(&self) -> bool {
+        self.light_properties.is_enabled
+    }
+
+    fn sample(&self, position: &Vec3, random: Vec2) -> LightSample {
+        match self.shape {
+            AreaLightShape::Rectangle { edge_u, edge_v } => {
+                let point_on_light =
+                    self.position + edge_u * (random.x - 0.5) + edge_v * (random.y - 0.5);
+                let normal = edge_u.cross(edge_v);
+                self.sample_flat_emitter(
+                    position,
+                    point_on_light,
+                    normal.normalize(),
+                    normal.length(),
+                )
+            }
+            AreaLightShape::Disk { normal, radius } => {
+                let (tangent, bitangent) = orthonormal_basis(normal);
+                let distance_from_center = radius * random.x.sqrt();
+                let angle = 2.0 * PI * random.y;
+                let point_on_light = self.position
+                    + (tangent * angle.cos() + bitangent * angle.sin()) * distance_from_center;
+                self.sample_flat_emitter(position, point_on_light, normal, PI * radius * radius)
+            }
+            AreaLightShape::Sphere { radius } => self.sample_sphere(position, radius, random),
+        }
+    }
+
+    fn get_samples_per_axis(&self) -> u32 {
+        self.samples_per_axis
+    }
+
+    fn hit_emitter(&self, ray: &Ray) -> Option<(f32, RGBColor)> {
+        let t = match self.shape {
+            AreaLightShape::Rectangle { edge_u, edge_v } => {
+                let normal = edge_u.cross(edge_v);
+                let t = hit_front_of_plane(ray, self.position, normal)?;
+                // Coordinates of the hitpoint along both edges, inside when both are within half an edge
+                let offset = ray.at(t) - self.position;
+                let inv_normal_length_sq = 1.0 / normal.length_squared();
+                let u = offset.cross(edge_v).dot(normal) * inv_normal_length_sq;
+                let v = edge_u.cross(offset).dot(normal) * inv_normal_length_sq;
+                (u.abs() <= 0.5 && v.abs() <= 0.5).then_some(t)?
+            }
+            AreaLightShape::Disk { normal, radius } => {
+                let t = hit_front_of_plane(ray, self.position, normal)?;
+                (ray.at(t).distance_squared(self.position) <= radius * radius).then_some(t)?
+            }
+            AreaLightShape::Sphere { radius } => {
+                let to_center = self.position - ray.origin;
+                let projection = to_center.dot(ray.direction);
+                let discriminant = projection
+                    .mul_add(projection, radius.mul_add(radius, -to_center.length_squared()));
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let half_chord = discriminant.sqrt();
+                [projection - half_chord, projection + half_chord]
+                    .into_iter()
+                    .find(|t| *t >= ray.t_min)?
+            }
+        };
+
+        Some((t, self.get_radiance()))
+    }
+}
+
+fn hit_front_of_plane(ray: &Ray, point: Vec3, normal: Vec3) -> Option<f32> {
+    let denominator = ray.direction.dot(normal);
+    if denominator >= 0.0 {
+        return None; // parallel or looking at the back of the light
+    }
+
+    let t = (point - ray.origin).dot(normal) / denominator;
+    (t >= ray.t_min).then_some(t)
+}
@@ -0,0 +1,252 @@
+use super::light_properties::{Light, LightProperties, LightSample, LightType};
+use crate::image_reader::HdrImage;
+use crate::math::ColorTypeFunctionality;
+use crate::ray::Ray;
+use glam::{EulerRot, Quat, Vec2, Vec3};
+use std::f32::consts::PI;
+pub type RGBColor = Vec3;
+
+/// Light arriving from infinitely far away in every direction, looked up in an equirectangular
+/// image. It is the background of the scene, shows up in reflections and is importance sampled by
+/// brightness so small bright areas like the sun cast shadows.
+#[derive(Clone, PartialEq)]
+pub struct EnvironmentLight {
+    pub light_properties: LightProperties,
+    image: HdrImage,
+    /// Euler angles in radians, edited like the position of other lights
+    rotation: Vec3,
+    samples_per_axis: u32,
+    distribution: EnvironmentDistribution,
+}
+
+impl EnvironmentLight {
+    pub fn new(
+        light_properties: LightProperties,
+        image: HdrImage,
+        rotation: Vec3,
+        samples_per_axis: u32,
+    ) -> Self {
+        let distribution = EnvironmentDistribution::new(&image);
+        Self {
+            light_properties,
+            image,
+            rotation,
+            samples_per_axis: samples_per_axis.max(1),
+            distribution,
+        }
+    }
+
+    fn get_rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.rotation.y, self.rotation.x, self.rotation.z)
+    }
+
+    /// Radiance arriving from `direction`, which points away from the scene.
+    fn get_radiance(&self, direction: Vec3) -> RGBColor {
+        let uv = direction_to_uv(self.get_rotation().inverse() * direction);
+        self.get_radiance_at_uv(uv)
+    }
+
+    /// Radiance of the pixel containing `uv`, unfiltered so it matches the sampling distribution.
+    /// Filtering would let a dark pixel next to the sun return a bright value with a tiny density.
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn get_pixel_radiance(&self, uv: Vec2) -> RGBColor {
+        let x = ((uv.x * self.image.width as f32) as u32).min(self.image.width - 1);
+        let y = ((uv.y * self.image.height as f32) as u32).min(self.image.height - 1);
+        self.image.get_pixel(x, y) * self.light_properties.color * self.light_properties.intensity
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn get_radiance_at_uv(&self, uv: Vec2) -> RGBColor {
+        // Bilinear filtering, wrapping around horizontally and clamping at the poles
+        let (width, height) = (self.image.width, self.image.height);
+        let x = uv.x.mul_add(width as f32, -0.5);
+        let y = uv.y.mul_add(height as f32, -0.5).clamp(0.0, (height - 1) as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let column = |offset: f32| ((x0 + offset) as i64).rem_euclid(i64::from(width)) as u32;
+        let (left, right) = (column(0.0), column(1.0));
+        let (top, bottom) = (y0 as u32, (y0 as u32 + 1).min(height - 1));
+
+        let upper = self.image.get_pixel(left, top).lerp(self.image.get_pixel(right, top), tx);
+        let lower =
+            self.image.get_pixel(left, bottom).lerp(self.image.get_pixel(right, bottom), tx);
+
+        upper.lerp(lower, ty) * self.light_properties.color * self.light_properties.intensity
+    }
+}
+
+impl Light for EnvironmentLight {
+    /// Irradiance on a surface facing up, assuming the sky is evenly bright.
+    fn get_bi_radians(&self, _position: &Vec3) -> RGBColor {
+        self.light_properties.color
+            * (self.light_properties.intensity * PI * self.distribution.average_luminance)
+    }
+
+    fn get_direction(&self, _position: &Vec3) -> Vec3 {
+        Vec3::Y
+    }
+
+    fn get_direction_magnitude(&self, _position: &Vec3, direction_magnitude: &mut f32) -> Vec3 {
+        *direction_magnitude = f32::INFINITY;
+        Vec3::Y
+    }
+
+    fn get_position(&self) -> &Vec3 {
+        &self.rotation
+    }
+
+    fn get_position_mut(&mut self) -> &mut Vec3 {
+        &mut self.rotation
+    }
+
+    fn get_color_mut(&mut self) -> &mut RGBColor {
+        &mut self.light_properties.color
+    }
+
+    fn get_intensity_mut(&mut self) -> &mut f32 {
+        &mut self.light_properties.intensity
+    }
+
+    fn toggle_light(&mut self) {
+        self.light_properties.toggle();
+    }
+
+    fn get_type_light(&self) -> LightType {
+        self.light_properties.light_type.clone()
+    }
+
+    fn is_light_enabled(&self) -> bool {
+        self.light_properties.is_enabled
+    }
+
+    fn sample(&self, _position: &Vec3, random: Vec2) -> LightSample {
+        let (uv, pdf_uv) = self.distribution.sample(random);
+
+        // The image covers 2π by π radians, which squeezes the rows near the poles
+        let sin_theta = (uv.y * PI).sin();
+        if pdf_uv <= 0.0 || sin_theta <= 0.0 {
+            return LightSample {
+                direction: Vec3::Y,
+                distance: f32::INFINITY,
+                bi_radiance: RGBColor::ZERO,
+            };
+        }
+        let pdf_solid_angle = pdf_uv / (2.0 * PI * PI * sin_theta);
+
+        let direction = self.get_rotation() * uv_to_direction(uv);
+        LightSample {
+            direction,
+            distance: f32::INFINITY,
+            bi_radiance: self.get_pixel_radiance(uv) / pdf_solid_angle,
+        }
+    }
+
+    fn get_samples_per_axis(&self) -> u32 {
+        self.samples_per_axis
+    }
+
+    fn hit_emitter(&self, ray: &Ray) -> Option<(f32, RGBColor)> {
+        // Behind everything, but still closer than a ray that hits nothing
+        Some((f32::MAX, self.get_radiance(ray.direction)))
+    }
+}
+
+/// Piecewise constant distribution over the pixels of the image, proportional to their luminance
+/// times the solid angle they cover.
+#[derive(Clone, PartialEq)]
+struct EnvironmentDistribution {
+    width: usize,
+    height: usize,
+    /// Cumulative distribution of every row, `width + 1` entries per row
+    row_cdfs: Vec<f32>,
+    /// Cumulative distribution over the rows, `height + 1` entries
+    marginal_cdf: Vec<f32>,
+    /// Probability of picking each pixel
+    pixel_probabilities: Vec<f32>,
+    average_luminance: f32,
+}
+
+impl EnvironmentDistribution {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(image: &HdrImage) -> Self {
+        let (width, height) = (image.width as usize, image.height as usize);
+
+        let luminances: Vec<f32> =
+            image.pixels.iter().map(ColorTypeFunctionality::luminance).collect();
+        let average_luminance = luminances.iter().sum::<f32>() / luminances.len() as f32;
+        // A small floor keeps completely black images from dividing by zero
+        let floor = average_luminance.max(f32::EPSILON) * 1e-3;
+
+        let mut weights = Vec::with_capacity(width * height);
+        for (y, row) in luminances.chunks_exact(width).enumerate() {
+            let sin_theta = (PI * (y as f32 + 0.5) / height as f32).sin();
+            weights.extend(row.iter().map(|luminance| (luminance + floor) * sin_theta));
+        }
+        let total_weight: f32 = weights.iter().sum();
+
+        let mut row_cdfs = Vec::with_capacity((width + 1) * height);
+        let mut marginal_cdf = Vec::with_capacity(height + 1);
+        marginal_cdf.push(0.0);
+        for row in weights.chunks_exact(width) {
+            let row_weight: f32 = row.iter().sum();
+            let mut cumulative = 0.0;
+            row_cdfs.push(0.0);
+            for weight in row {
+                cumulative += weight;
+                row_cdfs.push(cumulative / row_weight);
+            }
+            let previous = marginal_cdf.last().copied().unwrap_or_default();
+            marginal_cdf.push(previous + row_weight / total_weight);
+        }
+
+        Self {
+            width,
+            height,
+            row_cdfs,
+            marginal_cdf,
+            pixel_probabilities: weights.iter().map(|weight| weight / total_weight).collect(),
+            average_luminance,
+        }
+    }
+
+    /// Image coordinates in [0, 1)² and their probability density.
+    #[allow(clippy::cast_precision_loss)]
+    fn sample(&self, random: Vec2) -> (Vec2, f32) {
+        let (y, v) = sample_cdf(&self.marginal_cdf, random.y);
+        let row_cdf = &self.row_cdfs[y * (self.width + 1)..(y + 1) * (self.width + 1)];
+        let (x, u) = sample_cdf(row_cdf, random.x);
+
+        let pdf = self.pixel_probabilities[y * self.width + x] * (self.width * self.height) as f32;
+        (Vec2::new(u, v), pdf)
+    }
+}
+
+/// Picks the bucket of `cdf` that `random` falls into and where within the whole range it lands.
+#[allow(clippy::cast_precision_loss)]
+fn sample_cdf(cdf: &[f32], random: f32) -> (usize, f32) {
+    let bucket_count = cdf.len() - 1;
+    let bucket = cdf.partition_point(|value| *value <= random).clamp(1, bucket_count) - 1;
+
+    let (start, end) = (cdf[bucket], cdf[bucket + 1]);
+    let offset = if end > start { ((random - start) / (end - start)).clamp(0.0, 1.0) } else { 0.5 };
+    (bucket, (bucket as f32 + offset) / bucket_count as f32)
+}
+
+/// The center of the image lies towards -Z and the top row towards +Y.
+fn direction_to_uv(direction: Vec3) -> Vec2 {
+    Vec2::new(
+        0.5 + direction.x.atan2(-direction.z) / (2.0 * PI),
+        direction.y.clamp(-1.0, 1.0).acos() / PI,
+    )
+}
+
+fn uv_to_direction(uv: Vec2) -> Vec3 {
+    let phi = (uv.x - 0.5) * 2.0 * PI;
+    let theta = uv.y * PI;
+    Vec3::new(theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos())
+}
@@ -1,4 +1,4 @@
-use super::{light_properties::Light, LightEnum};
+use super::{light_properties::Light, sky_light::SkyLight, spot_light::SpotLight, LightEnum};
 use glam::Vec3;
 
 #[derive(Clone, PartialEq, Eq, Copy)]
@@ -11,12 +11,15 @@ pub enum Axis {
 enum InteractionMode {
     Position,
     Color,
+    Direction,
+    ConeAngles,
 }
 
 pub struct LightManager<T: Light> {
     lights: Vec<T>,
     selected_light_index: usize,
     interaction_mode: InteractionMode,
+    revision: u64,
 }
 
 type CommonLightManager = LightManager<LightEnum>;
@@ -27,16 +30,19 @@ impl<T: Light> LightManager<T> {
             lights: Vec::new(),
             selected_light_index: 0,
             interaction_mode: InteractionMode::Position,
+            revision: 0,
         }
     }
 
     pub fn add_light(&mut self, light: T) {
         self.lights.push(light);
+        self.revision += 1;
     }
 
     pub fn remove_light(&mut self, light: &T) {
         if let Some(index) = self.lights.iter().position(|l| std::ptr::eq(l, light)) {
             self.lights.remove(index);
+            self.revision += 1;
         }
     }
 
@@ -46,6 +52,8 @@ impl<T: Light> LightManager<T> {
 
     pub fn toggle_selected_light(&mut self) {
         self.lights[self.selected_light_index].toggle_light();
+        self.lights[self.selected_light_index].refresh();
+        self.revision += 1;
     }
 
     pub fn change_value_of_interaction_mode(&mut self, axis: Axis, value: f32) {
@@ -56,9 +64,17 @@ impl<T: Light> LightManager<T> {
             InteractionMode::Color => {
                 self.change_color_of_selected_light(axis, value);
             }
+            InteractionMode::Direction => {
+                self.change_direction_of_selected_light(axis, value);
+            }
+            InteractionMode::ConeAngles => {
+                self.change_cone_angles_of_selected_light(axis, value);
+            }
         }
+        self.lights[self.selected_light_index].refresh();
     }
     pub fn change_pos_selected_light(&mut self, axis: Axis, value: f32) {
+        self.revision += 1;
         let light = &mut self.lights[self.selected_light_index];
 
         // Pattern match on the light type
@@ -82,6 +98,7 @@ impl<T: Light> LightManager<T> {
     }
 
     pub fn change_color_of_selected_light(&mut self, axis: Axis, value: f32) {
+        self.revision += 1;
         let light = &mut self.lights[self.selected_light_index];
 
         let color = light.get_color_mut();
@@ -102,13 +119,85 @@ impl<T: Light> LightManager<T> {
         }
     }
 
+    /// Tilts the axis of the selected spot light, the direction is kept normalized.
+    pub fn change_direction_of_selected_light(&mut self, axis: Axis, value: f32) {
+        let light = &mut self.lights[self.selected_light_index];
+
+        let Some(direction) = light.get_spot_direction_mut() else {
+            println!("selected light has no direction to change");
+            return;
+        };
+
+        let mut new_direction = *direction;
+        match axis {
+            Axis::X => new_direction.x += value,
+            Axis::Y => new_direction.y += value,
+            Axis::Z => new_direction.z += value,
+        }
+
+        if let Some(new_direction) = new_direction.try_normalize() {
+            *direction = new_direction;
+            println!("selected light direction: {direction}");
+            self.revision += 1;
+        }
+    }
+
+    /// X changes the inner and Y the outer cone angle of the selected spot light, in steps of
+    /// `CONE_ANGLE_DEGREES_PER_UNIT` degrees per unit of `value`.
+    pub fn change_cone_angles_of_selected_light(&mut self, axis: Axis, value: f32) {
+        const CONE_ANGLE_DEGREES_PER_UNIT: f32 = 25.0;
+
+        let light = &mut self.lights[self.selected_light_index];
+
+        let Some((inner_cone_angle, outer_cone_angle)) = light.get_cone_angles_mut() else {
+            println!("selected light has no cone angles to change");
+            return;
+        };
+
+        let degrees = value * CONE_ANGLE_DEGREES_PER_UNIT;
+        match axis {
+            Axis::X => {
+                *inner_cone_angle =
+                    (*inner_cone_angle + degrees).clamp(0.0, SpotLight::MAX_CONE_ANGLE);
+                // Widening the inner cone past the outer one pushes the outer cone along
+                *outer_cone_angle = outer_cone_angle.max(*inner_cone_angle);
+            }
+            Axis::Y => {
+                *outer_cone_angle =
+                    (*outer_cone_angle + degrees).clamp(0.0, SpotLight::MAX_CONE_ANGLE);
+                *inner_cone_angle = inner_cone_angle.min(*outer_cone_angle);
+            }
+            Axis::Z => return,
+        }
+        println!("selected light cone angles: inner {inner_cone_angle}, outer {outer_cone_angle}");
+        self.revision += 1;
+    }
+
     pub fn change_intensity_of_selected_light(&mut self, value: f32) {
+        self.revision += 1;
         let light = &mut self.lights[self.selected_light_index];
 
         let intensity = light.get_intensity_mut();
 
         *intensity += value;
         println!("selected light intensity: {intensity}");
+        light.refresh();
+    }
+
+    pub fn set_light_color(&mut self, index: usize, color: Vec3) {
+        if let Some(light) = self.lights.get_mut(index) {
+            *light.get_color_mut() = color;
+            light.refresh();
+            self.revision += 1;
+        }
+    }
+
+    pub fn set_light_intensity(&mut self, index: usize, intensity: f32) {
+        if let Some(light) = self.lights.get_mut(index) {
+            *light.get_intensity_mut() = intensity;
+            light.refresh();
+            self.revision += 1;
+        }
     }
 
     pub fn num_lights(&self) -> usize {
@@ -122,6 +211,14 @@ impl<T: Light> LightManager<T> {
                 self.interaction_mode = InteractionMode::Color;
             }
             InteractionMode::Color => {
+                println!("Interaction mode changed to direction");
+                self.interaction_mode = InteractionMode::Direction;
+            }
+            InteractionMode::Direction => {
+                println!("Interaction mode changed to cone angles");
+                self.interaction_mode = InteractionMode::ConeAngles;
+            }
+            InteractionMode::ConeAngles => {
                 println!("Interaction mode changed to position");
                 self.interaction_mode = InteractionMode::Position;
             }
@@ -131,4 +228,39 @@ impl<T: Light> LightManager<T> {
     pub const fn get_lights(&self) -> &Vec<T> {
         &self.lights
     }
+
+    /// Increases on every change to the lights, so renderers can tell when their samples are stale.
+    pub const fn get_revision(&self) -> u64 {
+        self.revision
+    }
+}
+
+impl CommonLightManager {
+    pub fn change_sun_elevation(&mut self, degrees: f32) {
+        self.change_sky(|sky_light| sky_light.change_sun_elevation(degrees));
+    }
+
+    pub fn change_sun_azimuth(&mut self, degrees: f32) {
+        self.change_sky(|sky_light| sky_light.change_sun_azimuth(degrees));
+    }
+
+    pub fn change_sky_turbidity(&mut self, value: f32) {
+        self.change_sky(|sky_light| sky_light.change_turbidity(value));
+    }
+
+    /// Applies `change` to the first sky in the scene, the selected light doesn't matter since a
+    /// scene has at most one sky.
+    fn change_sky(&mut self, change: impl FnOnce(&mut SkyLight)) {
+        let sky_light = self.lights.iter_mut().find_map(|light| match light {
+            LightEnum::Sky(sky_light) => Some(sky_light),
+            _ => None,
+        });
+
+        if let Some(sky_light) = sky_light {
+            change(sky_light);
+            self.revision += 1;
+        } else {
+            println!("the scene has no sky");
+        }
+    }
 }
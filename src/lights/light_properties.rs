@@ -1,11 +1,16 @@
+use crate::ray::Ray;
 use enum_dispatch::enum_dispatch;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 pub type RGBColor = Vec3;
 
 #[derive(Clone, PartialEq, Eq)]
 pub enum LightType {
     Point,
     Directional,
+    Area,
+    Spot,
+    Environment,
+    Sky,
 }
 
 #[derive(Clone, PartialEq)]
@@ -16,6 +21,16 @@ pub struct LightProperties {
     pub light_type: LightType,
 }
 
+/// Light reaching a shading point from one point on a light.
+pub struct LightSample {
+    /// Normalized direction from the shading point towards the light
+    pub direction: Vec3,
+    /// Distance to the sampled point, the range of the shadow ray
+    pub distance: f32,
+    /// Irradiance on a surface facing the light, like `Light::get_bi_radians`
+    pub bi_radiance: RGBColor,
+}
+
 #[enum_dispatch]
 pub trait Light: Sync + Send {
     fn get_bi_radians(&self, position: &Vec3) -> RGBColor;
@@ -28,6 +43,39 @@ pub trait Light: Sync + Send {
     fn toggle_light(&mut self);
     fn get_type_light(&self) -> LightType;
     fn is_light_enabled(&self) -> bool;
+
+    /// Picks a point on the light for a shadow ray, `random` lies in [0, 1)². Lights without a
+    /// surface always return the same sample.
+    fn sample(&self, position: &Vec3, _random: Vec2) -> LightSample {
+        let mut distance = 0.0;
+        let direction = self.get_direction_magnitude(position, &mut distance);
+        LightSample { direction, distance, bi_radiance: self.get_bi_radians(position) }
+    }
+
+    /// Shading points cast `samples_per_axis²` stratified shadow rays towards the light.
+    fn get_samples_per_axis(&self) -> u32 {
+        1
+    }
+
+    /// Distance along `ray` to the surface of the light and the radiance it emits towards the
+    /// ray, `None` for lights that can't be seen.
+    fn hit_emitter(&self, _ray: &Ray) -> Option<(f32, RGBColor)> {
+        None
+    }
+
+    /// Recomputes whatever a light derives from its parameters, called after they were edited
+    /// through the `_mut` getters.
+    fn refresh(&mut self) {}
+
+    /// Axis a light shines along, `None` for lights that shine in every direction.
+    fn get_spot_direction_mut(&mut self) -> Option<&mut Vec3> {
+        None
+    }
+
+    /// Inner and outer cone angles in degrees, `None` for lights without a cone.
+    fn get_cone_angles_mut(&mut self) -> Option<(&mut f32, &mut f32)> {
+        None
+    }
 }
 
 impl LightProperties {
@@ -1,17 +1,30 @@
+pub mod area_light;
 pub mod directional_light;
+pub mod environment_light;
 pub mod light_manager;
 pub mod light_properties;
 pub mod point_light;
+pub mod sky_light;
+pub mod spot_light;
 
+use crate::ray::Ray;
+pub use area_light::AreaLight;
 pub use directional_light::DirectionalLight;
 use enum_dispatch::enum_dispatch;
-use glam::Vec3;
-pub use light_properties::{Light, LightProperties, LightType};
+pub use environment_light::EnvironmentLight;
+use glam::{Vec2, Vec3};
+pub use light_properties::{Light, LightProperties, LightSample, LightType};
 pub use point_light::PointLight;
+pub use sky_light::SkyLight;
+pub use spot_light::SpotLight;
 pub type RGBColor = Vec3;
 
 #[enum_dispatch(Light)]
 pub enum LightEnum {
     Point(PointLight),
     Directional(DirectionalLight),
+    Area(AreaLight),
+    Spot(SpotLight),
+    Environment(EnvironmentLight),
+    Sky(SkyLight),
 }
@@ -0,0 +1,325 @@
+use super::directional_light::DirectionalLight;
+use super::light_properties::{Light, LightProperties, LightSample, LightType};
+use crate::math::sampling::{cosine_hemisphere_pdf, cosine_sample_hemisphere};
+use crate::ray::Ray;
+use glam::{Mat3, Vec2, Vec3};
+use std::f32::consts::{FRAC_PI_2, PI};
+pub type RGBColor = Vec3;
+
+// Turbidity fits of the Perez coefficients for the x and y chromaticity and the luminance
+const PEREZ_X: [[f32; 2]; 5] = [
+    [-0.0193, -0.2592],
+    [-0.0665, 0.0008],
+    [-0.0004, 0.2125],
+    [-0.0641, -0.8989],
+    [-0.0033, 0.0452],
+];
+const PEREZ_Y: [[f32; 2]; 5] = [
+    [-0.0167, -0.2608],
+    [-0.0950, 0.0092],
+    [-0.0079, 0.2102],
+    [-0.0441, -1.6537],
+    [-0.0109, 0.0529],
+];
+const PEREZ_LUMINANCE: [[f32; 2]; 5] =
+    [[0.1787, -1.4630], [-0.3554, 0.4275], [-0.0227, 5.3251], [0.1206, -2.5771], [-0.0670, 0.3703]];
+
+/// Coefficients A to E of the Perez sky luminance distribution.
+#[derive(Clone, Copy, PartialEq)]
+struct PerezCoefficients([f32; 5]);
+
+impl PerezCoefficients {
+    /// Relative brightness of a direction at zenith angle `theta` and angle `gamma` from the sun.
+    fn evaluate(self, cos_theta: f32, gamma: f32) -> f32 {
+        let [darkening, gradient, circumsolar, circumsolar_width, backscatter] = self.0;
+        let cos_gamma = gamma.cos();
+        darkening.mul_add((gradient / cos_theta).exp(), 1.0)
+            * circumsolar.mul_add(
+                (circumsolar_width * gamma).exp(),
+                backscatter.mul_add(cos_gamma * cos_gamma, 1.0),
+            )
+    }
+}
+
+/// Clear sky following the analytic model of Preetham et al. 1999, lit by a sun that moves with
+/// it. The sky is the background of the scene and lights it from the upper hemisphere, the sun is
+/// a `DirectionalLight` whose color comes from the same atmosphere.
+#[derive(Clone, PartialEq)]
+pub struct SkyLight {
+    pub light_properties: LightProperties,
+    /// Degrees above the horizon
+    sun_elevation: f32,
+    /// Degrees clockwise from -Z when looking down
+    sun_azimuth: f32,
+    /// Haziness of the atmosphere, 2 is a very clear sky and 10 a hazy one
+    turbidity: f32,
+    samples_per_axis: u32,
+    /// Edited like the position of other lights, the elevation and azimuth follow it
+    direction_to_sun: Vec3,
+    // Model state derived from the parameters above
+    sun_transmittance: RGBColor,
+    zenith_xy_luminance: Vec3,
+    perez: [PerezCoefficients; 3],
+}
+
+impl SkyLight {
+    pub const MIN_TURBIDITY: f32 = 1.7;
+    pub const MAX_TURBIDITY: f32 = 10.0;
+    /// Brings the sky luminance of the model, in kcd/m², to the range of the other lights
+    const LUMINANCE_SCALE: f32 = 0.05;
+    /// Irradiance of the sun at full transmittance, relative to the sky intensity
+    const SUN_IRRADIANCE: f32 = 6.0;
+    /// Below the horizon the sky is replaced by ground reflecting this much of the horizon
+    const GROUND_ALBEDO: f32 = 0.3;
+
+    pub fn new(
+        light_properties: LightProperties,
+        sun_elevation: f32,
+        sun_azimuth: f32,
+        turbidity: f32,
+        samples_per_axis: u32,
+    ) -> Self {
+        let mut sky_light = Self {
+            light_properties,
+            sun_elevation,
+            sun_azimuth,
+            turbidity,
+            samples_per_axis: samples_per_axis.max(1),
+            direction_to_sun: Vec3::Y,
+            sun_transmittance: RGBColor::ZERO,
+            zenith_xy_luminance: Vec3::ZERO,
+            perez: [PerezCoefficients([0.0; 5]); 3],
+        };
+        sky_light.update_model();
+        sky_light
+    }
+
+    /// The sun belonging to the current state of the sky.
+    pub fn get_sun(&self) -> DirectionalLight {
+        DirectionalLight::new(
+            LightProperties::new(
+                self.sun_transmittance * self.light_properties.color,
+                Self::SUN_IRRADIANCE * self.light_properties.intensity,
+                self.light_properties.is_enabled && self.sun_elevation > 0.0,
+                LightType::Directional,
+            ),
+            -self.direction_to_sun,
+        )
+    }
+
+    pub fn change_sun_elevation(&mut self, degrees: f32) {
+        self.sun_elevation = (self.sun_elevation + degrees).clamp(-10.0, 90.0);
+        self.update_model();
+        println!("sun elevation: {}", self.sun_elevation);
+    }
+
+    pub fn change_sun_azimuth(&mut self, degrees: f32) {
+        self.sun_azimuth = (self.sun_azimuth + degrees).rem_euclid(360.0);
+        self.update_model();
+        println!("sun azimuth: {}", self.sun_azimuth);
+    }
+
+    pub fn change_turbidity(&mut self, value: f32) {
+        self.turbidity = (self.turbidity + value).clamp(Self::MIN_TURBIDITY, Self::MAX_TURBIDITY);
+        self.update_model();
+        println!("sky turbidity: {}", self.turbidity);
+    }
+
+    /// Recomputes the sun direction, the zenith values, the distribution coefficients and the
+    /// color of the sun.
+    #[allow(clippy::suboptimal_flops)]
+    fn update_model(&mut self) {
+        let (elevation, azimuth) = (self.sun_elevation.to_radians(), self.sun_azimuth.to_radians());
+        self.direction_to_sun = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+
+        // The fits only hold for a sun above the horizon
+        let theta_sun = FRAC_PI_2 - elevation.max(0.0);
+        let turbidity = self.turbidity;
+
+        let chi = (4.0 / 9.0 - turbidity / 120.0) * (PI - 2.0 * theta_sun);
+        let zenith_luminance =
+            ((4.0453 * turbidity - 4.9710) * chi.tan() - 0.2155 * turbidity + 2.4192).max(0.0);
+
+        let theta = Vec3::new(theta_sun.powi(3), theta_sun.powi(2), theta_sun);
+        let chromaticity = |rows: [[f32; 4]; 3]| {
+            let weights = Vec3::new(turbidity * turbidity, turbidity, 1.0);
+            let per_row = rows.map(|[a, b, c, d]| theta.dot(Vec3::new(a, b, c)) + d);
+            weights.dot(Vec3::from_array(per_row))
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+        self.zenith_xy_luminance = Vec3::new(zenith_x, zenith_y, zenith_luminance);
+
+        // Each coefficient is `slope * turbidity + offset`
+        self.perez = [PEREZ_X, PEREZ_Y, PEREZ_LUMINANCE].map(|coefficients| {
+            PerezCoefficients(coefficients.map(|[slope, offset]| slope.mul_add(turbidity, offset)))
+        });
+
+        self.sun_transmittance = self.get_sun_transmittance();
+    }
+
+    /// Fraction of red, green and blue sunlight that makes it through the atmosphere, from
+    /// Rayleigh scattering by air and Mie scattering by haze.
+    #[allow(clippy::suboptimal_flops)]
+    fn get_sun_transmittance(&self) -> RGBColor {
+        if self.sun_elevation <= 0.0 {
+            return RGBColor::ZERO;
+        }
+
+        let zenith_degrees = 90.0 - self.sun_elevation;
+        let relative_optical_mass = 1.0
+            / (zenith_degrees.to_radians().cos() + 0.15 * (93.885 - zenith_degrees).powf(-1.253));
+        let haze = 0.046_08 * self.turbidity - 0.045_86;
+
+        // Wavelengths of red, green and blue in micrometers
+        let wavelengths = Vec3::new(0.65, 0.57, 0.475);
+        let optical_depth = wavelengths.to_array().map(|wavelength| {
+            let rayleigh = 0.008_735 * wavelength.powf(-4.08);
+            let mie = haze * wavelength.powf(-1.3);
+            (rayleigh + mie) * relative_optical_mass
+        });
+
+        (-Vec3::from_array(optical_depth)).exp()
+    }
+
+    /// Radiance of the sky seen along `direction`.
+    fn get_radiance(&self, direction: Vec3) -> RGBColor {
+        // Below the horizon the model is undefined, the ground reflects the horizon instead
+        let is_ground = direction.y < 0.0;
+        let cos_theta = direction.y.abs().max(0.01);
+        let direction = Vec3::new(direction.x, cos_theta, direction.z).normalize();
+
+        let gamma = direction.dot(self.direction_to_sun).clamp(-1.0, 1.0).acos();
+        let theta_sun = FRAC_PI_2 - self.sun_elevation.to_radians().max(0.0);
+
+        let [x, y, luminance] = [0, 1, 2].map(|i| {
+            self.zenith_xy_luminance[i] * self.perez[i].evaluate(cos_theta, gamma)
+                / self.perez[i].evaluate(1.0, theta_sun)
+        });
+
+        let mut radiance = xyy_to_linear_srgb(x, y, luminance)
+            * (Self::LUMINANCE_SCALE * self.light_properties.intensity)
+            * self.light_properties.color;
+        if is_ground {
+            radiance *= Self::GROUND_ALBEDO;
+        }
+        radiance.max(RGBColor::ZERO)
+    }
+
+    /// Light arriving from the sky dome, sampled proportionally to the cosine with the zenith.
+    fn sample_sky(&self, random: Vec2) -> LightSample {
+        let direction = cosine_sample_hemisphere(Vec3::Y, random);
+        let pdf = cosine_hemisphere_pdf(Vec3::Y, direction);
+        let bi_radiance =
+            if pdf > 0.0 { self.get_radiance(direction) / pdf } else { RGBColor::ZERO };
+        LightSample { direction, distance: f32::INFINITY, bi_radiance }
+    }
+}
+
+impl Light for SkyLight {
+    fn get_bi_radians(&self, _position: &Vec3) -> RGBColor {
+        self.get_radiance(Vec3::Y) * PI
+    }
+
+    fn get_direction(&self, _position: &Vec3) -> Vec3 {
+        self.direction_to_sun
+    }
+
+    fn get_direction_magnitude(&self, _position: &Vec3, direction_magnitude: &mut f32) -> Vec3 {
+        *direction_magnitude = f32::INFINITY;
+        self.direction_to_sun
+    }
+
+    fn get_position(&self) -> &Vec3 {
+        &self.direction_to_sun
+    }
+
+    fn get_position_mut(&mut self) -> &mut Vec3 {
+        &mut self.direction_to_sun
+    }
+
+    fn get_color_mut(&mut self) -> &mut RGBColor {
+        &mut self.light_properties.color
+    }
+
+    fn get_intensity_mut(&mut self) -> &mut f32 {
+        &mut self.light_properties.intensity
+    }
+
+    fn toggle_light(&mut self) {
+        self.light_properties.toggle();
+    }
+
+    fn get_type_light(&self) -> LightType {
+        self.light_properties.light_type.clone()
+    }
+
+    fn is_light_enabled(&self) -> bool {
+        self.light_properties.is_enabled
+    }
+
+    /// Splits the random numbers between the sun and the sky, so the stratified samples of the
+    /// Whitted renderer always cover both.
+    fn sample(&self, position: &Vec3, random: Vec2) -> LightSample {
+        let sun = self.get_sun();
+        let sun_probability = if sun.is_light_enabled() { 0.5 } else { 0.0 };
+
+        if random.x < sun_probability {
+            let mut sun_sample = sun.sample(position, random);
+            sun_sample.bi_radiance /= sun_probability;
+            return sun_sample;
+        }
+
+        let random = Vec2::new((random.x - sun_probability) / (1.0 - sun_probability), random.y);
+        let mut sky_sample = self.sample_sky(random);
+        sky_sample.bi_radiance /= 1.0 - sun_probability;
+        sky_sample
+    }
+
+    fn get_samples_per_axis(&self) -> u32 {
+        self.samples_per_axis
+    }
+
+    fn hit_emitter(&self, ray: &Ray) -> Option<(f32, RGBColor)> {
+        // Behind everything, but still closer than a ray that hits nothing
+        Some((f32::MAX, self.get_radiance(ray.direction)))
+    }
+
+    /// The sun direction may have been moved, the elevation and azimuth are taken from it.
+    fn refresh(&mut self) {
+        let Some(direction_to_sun) = self.direction_to_sun.try_normalize() else {
+            self.update_model();
+            return;
+        };
+        self.sun_elevation = direction_to_sun.y.clamp(-1.0, 1.0).asin().to_degrees();
+        self.sun_azimuth =
+            direction_to_sun.x.atan2(-direction_to_sun.z).to_degrees().rem_euclid(360.0);
+        self.update_model();
+    }
+}
+
+fn xyy_to_linear_srgb(x: f32, y: f32, luminance: f32) -> RGBColor {
+    if y <= 0.0 {
+        return RGBColor::ZERO;
+    }
+
+    let xyz = Vec3::new(x * luminance / y, luminance, (1.0 - x - y) * luminance / y);
+    let xyz_to_srgb = Mat3::from_cols(
+        Vec3::new(3.2406, -0.9689, 0.0557),
+        Vec3::new(-1.5372, 1.8758, -0.2040),
+        Vec3::new(-0.4986, 0.0415, 1.0570),
+    );
+    xyz_to_srgb * xyz
+}
@@ -0,0 +1,110 @@
+use super::light_properties::{Light, LightProperties, LightType};
+use glam::Vec3;
+pub type RGBColor = Vec3;
+
+/// Point light that only shines inside a cone. Full intensity inside the inner cone, fading out
+/// smoothly towards the outer cone. Both angles are measured from the cone axis, in degrees.
+#[derive(Clone, PartialEq)]
+pub struct SpotLight {
+    pub light_properties: LightProperties,
+    position: Vec3,
+    direction: Vec3,
+    inner_cone_angle: f32,
+    outer_cone_angle: f32,
+}
+
+impl SpotLight {
+    pub const MAX_CONE_ANGLE: f32 = 90.0;
+
+    pub fn new(
+        light_properties: LightProperties,
+        position: Vec3,
+        direction: Vec3,
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    ) -> Self {
+        let mut spot_light = Self {
+            light_properties,
+            position,
+            direction: direction.normalize(),
+            inner_cone_angle,
+            outer_cone_angle,
+        };
+        spot_light.clamp_cone_angles();
+        spot_light
+    }
+
+    /// Keeps both angles within 0-90 degrees and the inner cone inside the outer one.
+    const fn clamp_cone_angles(&mut self) {
+        self.outer_cone_angle = self.outer_cone_angle.clamp(0.0, Self::MAX_CONE_ANGLE);
+        self.inner_cone_angle = self.inner_cone_angle.clamp(0.0, self.outer_cone_angle);
+    }
+
+    fn get_cone_falloff(&self, position: &Vec3) -> f32 {
+        let cos_angle = (*position - self.position).normalize_or_zero().dot(self.direction);
+        let cos_outer = self.outer_cone_angle.to_radians().cos();
+        let cos_inner = self.inner_cone_angle.to_radians().cos();
+
+        if cos_inner - cos_outer <= f32::EPSILON {
+            return if cos_angle >= cos_outer { 1.0 } else { 0.0 };
+        }
+
+        // Smoothstep between the edges of both cones
+        let t = ((cos_angle - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0);
+        t * t * 2.0f32.mul_add(-t, 3.0)
+    }
+}
+
+impl Light for SpotLight {
+    fn get_bi_radians(&self, position: &Vec3) -> RGBColor {
+        let direction_sq = position.distance_squared(self.position);
+        self.light_properties.color
+            * (self.light_properties.intensity * self.get_cone_falloff(position) / direction_sq)
+    }
+
+    fn get_direction(&self, position: &Vec3) -> Vec3 {
+        (self.position - *position).normalize()
+    }
+
+    fn get_direction_magnitude(&self, position: &Vec3, direction_magnitude: &mut f32) -> Vec3 {
+        let direction = self.position - *position;
+        *direction_magnitude = direction.length();
+        direction.normalize_or_zero()
+    }
+
+    fn get_position(&self) -> &Vec3 {
+        &self.position
+    }
+
+    fn get_position_mut(&mut self) -> &mut Vec3 {
+        &mut self.position
+    }
+
+    fn get_color_mut(&mut self) -> &mut RGBColor {
+        &mut self.light_properties.color
+    }
+
+    fn get_intensity_mut(&mut self) -> &mut f32 {
+        &mut self.light_properties.intensity
+    }
+
+    fn toggle_light(&mut self) {
+        self.light_properties.toggle();
+    }
+
+    fn get_type_light(&self) -> LightType {
+        self.light_properties.light_type.clone()
+    }
+
+    fn is_light_enabled(&self) -> bool {
+        self.light_properties.is_enabled
+    }
+
+    fn get_spot_direction_mut(&mut self) -> Option<&mut Vec3> {
+        Some(&mut self.direction)
+    }
+
+    fn get_cone_angles_mut(&mut self) -> Option<(&mut f32, &mut f32)> {
+        Some((&mut self.inner_cone_angle, &mut self.outer_cone_angle))
+    }
+}
@@ -11,19 +11,33 @@
     clippy::correctness,
     clippy::suspicious
 )]
+// Duplicates that can't be lined up from this manifest: png depends on miniz_oxide directly and
+// through flate2, which moves to the next miniz_oxide version before png does, and newer
+// serde_derive releases build on syn 3 while enum_dispatch still uses syn 2
+#![allow(clippy::multiple_crate_versions)]
 
+mod animation;
+mod aov;
 mod camera;
 mod canvas;
+mod command_line;
+mod headless;
 mod hitrecord;
+mod image_reader;
+mod image_writer;
 mod lights;
 mod materials;
 mod math;
 mod objects;
+mod projection;
 mod ray;
 mod renderer;
+mod textures;
+mod video_writer;
 mod world;
 
 use canvas::Canvas;
+use command_line::CommandLineOptions;
 use glam::{IVec2, Vec3};
 use lights::{
     light_manager::{Axis, LightManager},
@@ -34,7 +48,9 @@ use materials::material_manager::MaterialManager;
 use renderer::Renderer;
 use sdl2::{event::Event, keyboard::Keycode, mouse::MouseButton};
 
+use std::path::Path;
 use world::{
+    scene_file::SceneDescription,
     scene_manager::SceneManager,
     world_creation::{create_lights, create_materials, create_scenes},
 };
@@ -47,26 +63,81 @@ fn print_key_mapping() {
               ---- The following keys correspond to the corresponding actions ----\n\n\
               Z: Turn off shadow casting \n\
               C: Change the amount of bounces (1,2,3,4,5,6 bounces) \n\
-              V: Change Render modes (irradiance only, BRDF only and all) \n\n\
+              V: Change Render modes (irradiance only, BRDF only and all) \n\
+              T: Switch between the Whitted ray tracer and the progressive path tracer \n\
+              X: Change the amount of anti-aliasing samples per pixel (1,4,9,16 samples) \n\
+              F: Change the anti-aliasing filter (box, tent, Gaussian and Mitchell-Netravali) \n\n\
               P: Cycle through the lights in the scene to choose which one to select \n\
-              O: Switch between 'Change Color' or 'Change Position' for the selected light \n\
+              O: Cycle between 'Change Position', 'Change Color', 'Change Direction' and 'Change Cone Angles' for the selected light \n\
               Y: Go to previous scene\n\
               U: Go to next scene\n\
               0: Turn off selected light \n\n\
               1 & 2: Change x/r value of the selected light pos/color \n\
               3 & 4: Change y/g value of the selected light pos/color \n\
               5 & 6: Change z/b value of the selected light pos/color \n\
+              In cone angle mode 1 & 2 change the inner and 3 & 4 the outer cone angle of a spot light \n\
               7 & 8: Change intensity of the selected light \n\n\
+              I & K: Raise or lower the sun of the sky \n\
+              J & L: Move the sun of the sky around the horizon \n\
+              G & H: Make the sky clearer or hazier \n\n\
+              [ & ]: Close or open the aperture of the camera for depth of field \n\
+              - & =: Move the focal plane closer or further away \n\
+              R: Turn autofocus on the object in the center of the screen on or off \n\
+              B: Cycle the camera projection (perspective, orthographic, equirectangular, fisheye and cube map faces) \n\
+              Space: Play or pause the animation of the scene \n\
+              , & .: Scrub the animation a quarter of a second backward or forward \n\n\
+              Up & Down: Raise or lower the exposure by half a stop \n\
+              9: Change the tone mapper (normalize, Reinhard, ACES, Uncharted 2 and AgX) \n\
+              \\: Switch between sRGB and linear output \n\
+              Tab: Cycle the displayed pass (beauty, depth, normal, albedo, object ID, material ID, UV, direct, indirect and shadow mask) \n\
+              F12: Save the unprocessed framebuffer of the current pass to render.exr \n\n\
               M: Print key mapping again \n\
               N: Print FPS \n\n"
     );
 }
 
+/// Writes the framebuffer as is, without exposure or tone mapping, for compositing.
+fn save_framebuffer(render_system: &Renderer, width: u32, height: u32) {
+    let path = Path::new("render.exr");
+    match image_writer::write_hdr_image(path, width, height, render_system.get_framebuffer()) {
+        Ok(()) => println!("Saved {}", path.display()),
+        Err(error) => eprintln!("Saving {} failed: {error}", path.display()),
+    }
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let options = match CommandLineOptions::from_args(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}\n\n{}", command_line::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(headless_options) = &options.headless {
+        if let Err(error) =
+            headless::render_to_file(headless_options, options.scene_file.as_deref())
+        {
+            eprintln!("Headless render failed: {error}");
+            std::process::exit(1);
+        }
+    } else if let Err(error) = run_interactive(options.scene_file.as_deref()) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn run_interactive(scene_file: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    // Load the scene file before opening the window so mistakes in it are reported right away
+    let scene_description = scene_file.map(SceneDescription::from_file).transpose()?;
+
     let width = 800;
     let height = 600;
-    let mut canvas = Canvas::new(width, height).unwrap();
-    let mut event_pump = canvas.sdl_context.event_pump().unwrap();
+    let mut canvas = Canvas::new(width, height)?;
+    let mut event_pump = canvas.sdl_context.event_pump()?;
 
     let mut frame_count = 0u32;
     let mut last_fps_time;
@@ -78,18 +149,23 @@ fn main() {
 
     previous_time = last_fps_time;
 
-    let mut camera = camera::Camera::new(Vec3::new(0.0, 2.0, 15.0), 45.0);
-
     let mut delta_time: f32 = 0.001;
 
     let mut material_manager: MaterialManager<'_> = MaterialManager::new();
-    create_materials(&mut material_manager);
-
     let mut light_manager: LightManager<LightEnum> = LightManager::new();
-    create_lights(&mut light_manager);
-
     let mut scene_manager: SceneManager<'_> = SceneManager::new();
-    create_scenes(&mut scene_manager, &material_manager);
+
+    let mut camera = if let Some(scene_description) = &scene_description {
+        scene_description.create_materials(&mut material_manager)?;
+        scene_description.create_lights(&mut light_manager)?;
+        scene_description.create_scene(&mut scene_manager, &material_manager)?;
+        scene_description.create_camera()
+    } else {
+        create_materials(&mut material_manager);
+        create_lights(&mut light_manager);
+        create_scenes(&mut scene_manager, &material_manager);
+        camera::Camera::new(Vec3::new(0.0, 2.0, 15.0), 45.0)
+    };
 
     let mut render_system = Renderer::new(width, height);
     let mut prev_mouse_x = 0;
@@ -108,11 +184,20 @@ fn main() {
                     Keycode::W | Keycode::S | Keycode::A | Keycode::D | Keycode::Q | Keycode::E => {
                         camera.camera_translation(delta_time, key);
                     }
-                    Keycode::Y => scene_manager.previous_scene(),
-                    Keycode::U => scene_manager.next_scene(),
+                    Keycode::Y => {
+                        scene_manager.previous_scene();
+                        render_system.reset_accumulation();
+                    }
+                    Keycode::U => {
+                        scene_manager.next_scene();
+                        render_system.reset_accumulation();
+                    }
                     Keycode::Z => render_system.toggle_shadows(),
                     Keycode::V => render_system.toggle_render_mode(),
                     Keycode::C => render_system.toggle_max_bounce_rays(),
+                    Keycode::T => render_system.toggle_integrator(),
+                    Keycode::X => render_system.toggle_pixel_samples(),
+                    Keycode::F => render_system.toggle_pixel_filter(),
                     Keycode::P => light_manager.next_selected_light(),
                     Keycode::O => light_manager.change_interaction_mode(),
                     Keycode::Num0 => light_manager.toggle_selected_light(),
@@ -124,6 +209,33 @@ fn main() {
                     Keycode::Num6 => light_manager.change_value_of_interaction_mode(Axis::Z, 0.04),
                     Keycode::Num7 => light_manager.change_intensity_of_selected_light(-0.50),
                     Keycode::Num8 => light_manager.change_intensity_of_selected_light(0.50),
+                    Keycode::I => light_manager.change_sun_elevation(2.0),
+                    Keycode::K => light_manager.change_sun_elevation(-2.0),
+                    Keycode::L => light_manager.change_sun_azimuth(5.0),
+                    Keycode::J => light_manager.change_sun_azimuth(-5.0),
+                    Keycode::H => light_manager.change_sky_turbidity(0.5),
+                    Keycode::G => light_manager.change_sky_turbidity(-0.5),
+                    Keycode::LeftBracket => camera.change_aperture_radius(-0.02),
+                    Keycode::RightBracket => camera.change_aperture_radius(0.02),
+                    Keycode::Minus => camera.change_focal_distance(-0.25),
+                    Keycode::Equals => camera.change_focal_distance(0.25),
+                    Keycode::R => camera.toggle_autofocus(),
+                    Keycode::B => camera.toggle_projection(),
+                    Keycode::Space => {
+                        scene_manager.get_current_scene_mut().get_animation_mut().toggle_playback();
+                    }
+                    Keycode::Comma => {
+                        scene_manager.get_current_scene_mut().get_animation_mut().scrub(-0.25);
+                    }
+                    Keycode::Period => {
+                        scene_manager.get_current_scene_mut().get_animation_mut().scrub(0.25);
+                    }
+                    Keycode::Up => render_system.change_exposure(0.5),
+                    Keycode::Down => render_system.change_exposure(-0.5),
+                    Keycode::Num9 => render_system.toggle_tone_mapper(),
+                    Keycode::Backslash => render_system.toggle_srgb(),
+                    Keycode::Tab => render_system.toggle_aov(),
+                    Keycode::F12 => save_framebuffer(&render_system, width, height),
                     Keycode::M => print_key_mapping(),
                     Keycode::N => should_print_fps = !should_print_fps,
                     _ => {}
@@ -143,16 +255,13 @@ fn main() {
             prev_mouse_y = mouse_y;
         }
 
-        camera.update_look_at();
+        scene_manager.update(delta_time, &mut camera, &mut light_manager);
 
-        scene_manager.update(delta_time);
+        camera.update_autofocus(scene_manager.get_current_scene());
+        camera.update_look_at();
 
-        render_system.render(
-            canvas.get_pixel_data_mut(),
-            scene_manager.get_current_scene(),
-            &camera,
-            light_manager.get_lights(),
-        );
+        render_system.render(scene_manager.get_current_scene(), &camera, &light_manager);
+        render_system.write_display_pixels(canvas.get_pixel_data_mut());
 
         canvas.flush();
 
@@ -176,4 +285,6 @@ fn main() {
             }
         }
     }
+
+    Ok(())
 }
@@ -0,0 +1,80 @@
+use crate::hitrecord::HitRecord;
+use crate::materials::material_properties::{
+    Material, MaterialProperties, MaterialTextures, RGBColor,
+};
+use crate::math::brdf;
+use glam::Vec3;
+
+/// Transparent material such as glass or water. Light hitting it is split between a mirror
+/// reflection and a refracted ray according to the Fresnel equations, the refracted part is
+/// attenuated by Beer–Lambert absorption while it travels through the object.
+pub struct DielectricMaterial {
+    pub properties: MaterialProperties,
+    pub index_of_refraction: f32,
+    pub absorption: RGBColor,
+    pub specular_reflectance: f32,
+    pub phong_exponent: i32,
+}
+
+impl DielectricMaterial {
+    pub const fn new(
+        properties: MaterialProperties,
+        index_of_refraction: f32,
+        absorption: RGBColor,
+        specular_reflectance: f32,
+        phong_exponent: i32,
+    ) -> Self {
+        Self { properties, index_of_refraction, absorption, specular_reflectance, phong_exponent }
+    }
+}
+
+impl Material for DielectricMaterial {
+    // Only the highlights of the lights, everything else comes from the reflected and refracted rays
+    fn shade(
+        &self,
+        hitrecord: &HitRecord<'_>,
+        light_direction: &Vec3,
+        view_direction: &Vec3,
+    ) -> RGBColor {
+        brdf::phong(
+            self.specular_reflectance,
+            self.phong_exponent,
+            light_direction,
+            view_direction,
+            &hitrecord.normal,
+        )
+    }
+
+    fn get_reflectiveness_environment(&self, hitrecord: &HitRecord<'_>) -> f32 {
+        self.properties.get_reflectiveness_environment(hitrecord.uv)
+    }
+
+    fn get_albedo(&self, _hitrecord: &HitRecord<'_>) -> RGBColor {
+        // What survives absorption, clear glass is white
+        self.properties.diffuse_color
+    }
+
+    fn get_id(&self) -> u32 {
+        self.properties.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.properties.id = id;
+    }
+
+    fn set_textures(&mut self, textures: MaterialTextures) {
+        self.properties.textures = textures;
+    }
+
+    fn perturb_normal(&self, hitrecord: &mut HitRecord<'_>, ray_direction: Vec3) {
+        self.properties.textures.perturb_normal(hitrecord, ray_direction);
+    }
+
+    fn get_index_of_refraction(&self) -> Option<f32> {
+        Some(self.index_of_refraction)
+    }
+
+    fn get_absorption(&self) -> RGBColor {
+        self.absorption
+    }
+}
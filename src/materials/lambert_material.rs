@@ -1,5 +1,5 @@
 use crate::hitrecord::HitRecord;
-use crate::materials::material_properties::{Material, MaterialProperties};
+use crate::materials::material_properties::{Material, MaterialProperties, MaterialTextures};
 use crate::math::brdf;
 use glam::Vec3;
 pub type RGBColor = Vec3;
@@ -19,6 +19,8 @@ impl LambertMaterial {
                 diffuse_color,
                 diffuse_reflectance,
                 reflectiveness_environment,
+                id: 0,
+                textures: MaterialTextures::new(),
             },
         }
     }
@@ -26,14 +28,37 @@ impl LambertMaterial {
 impl Material for LambertMaterial {
     fn shade(
         &self,
-        _hitrecord: &HitRecord<'_>,
+        hitrecord: &HitRecord<'_>,
         _light_direction: &Vec3,
         _view_direction: &Vec3,
     ) -> RGBColor {
-        brdf::lambert(&self.properties.diffuse_color, self.properties.diffuse_reflectance)
+        brdf::lambert(
+            &self.properties.get_diffuse_color(hitrecord.uv),
+            self.properties.diffuse_reflectance,
+        )
     }
 
-    fn get_reflectiveness_environment(&self) -> f32 {
-        self.properties.reflectiveness_environment
+    fn get_reflectiveness_environment(&self, hitrecord: &HitRecord<'_>) -> f32 {
+        self.properties.get_reflectiveness_environment(hitrecord.uv)
+    }
+
+    fn get_albedo(&self, hitrecord: &HitRecord<'_>) -> RGBColor {
+        self.properties.get_diffuse_color(hitrecord.uv) * self.properties.diffuse_reflectance
+    }
+
+    fn get_id(&self) -> u32 {
+        self.properties.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.properties.id = id;
+    }
+
+    fn set_textures(&mut self, textures: MaterialTextures) {
+        self.properties.textures = textures;
+    }
+
+    fn perturb_normal(&self, hitrecord: &mut HitRecord<'_>, ray_direction: Vec3) {
+        self.properties.textures.perturb_normal(hitrecord, ray_direction);
     }
 }
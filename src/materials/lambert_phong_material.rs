@@ -1,6 +1,9 @@
 use crate::hitrecord::HitRecord;
-use crate::materials::material_properties::{Material, MaterialProperties, RGBColor};
-use crate::math::brdf;
+use crate::materials::material_properties::{
+    weigh_brdf_sample, BrdfSample, Material, MaterialProperties, MaterialTextures, RGBColor,
+};
+use crate::math::sampling::{self, Sampler};
+use crate::math::{brdf, reflect};
 use glam::Vec3;
 
 pub struct LambertPhongMaterial {
@@ -25,17 +28,65 @@ impl Material for LambertPhongMaterial {
         light_direction: &Vec3,
         view_direction: &Vec3,
     ) -> RGBColor {
-        brdf::lambert(&self.properties.diffuse_color, self.properties.diffuse_reflectance)
-            + brdf::phong(
-                self.specular_reflectance,
-                self.phong_exponent,
-                light_direction,
-                view_direction,
-                &hitrecord.normal,
-            )
-    }
-
-    fn get_reflectiveness_environment(&self) -> f32 {
-        self.properties.reflectiveness_environment
+        brdf::lambert(
+            &self.properties.get_diffuse_color(hitrecord.uv),
+            self.properties.diffuse_reflectance,
+        ) + brdf::phong(
+            self.specular_reflectance,
+            self.phong_exponent,
+            light_direction,
+            view_direction,
+            &hitrecord.normal,
+        )
+    }
+
+    fn get_reflectiveness_environment(&self, hitrecord: &HitRecord<'_>) -> f32 {
+        self.properties.get_reflectiveness_environment(hitrecord.uv)
+    }
+
+    fn get_albedo(&self, hitrecord: &HitRecord<'_>) -> RGBColor {
+        self.properties.get_diffuse_color(hitrecord.uv) * self.properties.diffuse_reflectance
+    }
+
+    fn get_id(&self) -> u32 {
+        self.properties.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.properties.id = id;
+    }
+
+    fn set_textures(&mut self, textures: MaterialTextures) {
+        self.properties.textures = textures;
+    }
+
+    fn perturb_normal(&self, hitrecord: &mut HitRecord<'_>, ray_direction: Vec3) {
+        self.properties.textures.perturb_normal(hitrecord, ray_direction);
+    }
+
+    fn sample_brdf(
+        &self,
+        hitrecord: &HitRecord<'_>,
+        view_direction: &Vec3,
+        sampler: &mut Sampler,
+    ) -> Option<BrdfSample> {
+        let mirror_direction = reflect(-*view_direction, hitrecord.normal);
+        let diffuse_weight = self.properties.get_diffuse_color(hitrecord.uv).max_element()
+            * self.properties.diffuse_reflectance;
+        let specular_probability = self.specular_reflectance
+            / (self.specular_reflectance + diffuse_weight).max(f32::EPSILON);
+
+        let direction = if sampler.next_f32() < specular_probability {
+            sampling::sample_phong_lobe(mirror_direction, self.phong_exponent, sampler.next_vec2())
+        } else {
+            sampling::cosine_sample_hemisphere(hitrecord.normal, sampler.next_vec2())
+        };
+
+        let pdf = specular_probability.mul_add(
+            sampling::phong_lobe_pdf(mirror_direction, self.phong_exponent, direction),
+            (1.0 - specular_probability)
+                * sampling::cosine_hemisphere_pdf(hitrecord.normal, direction),
+        );
+        weigh_brdf_sample(self, hitrecord, view_direction, direction, pdf)
     }
 }
@@ -1,12 +1,14 @@
+use super::dielectric_material::DielectricMaterial;
 use super::lambert_material::LambertMaterial;
 use super::lambert_phong_material::LambertPhongMaterial;
 use super::material_definitions::{
     create_diffuse_rgb_hash_map, create_linear_fresnel_hash_map, RGBColor,
 };
-use super::material_properties::MaterialProperties;
+use super::material_properties::{Material, MaterialProperties};
 use super::phong_brdf_material::PhongBRDFMaterial;
 use super::MaterialEnum;
-use std::collections::HashMap;
+use crate::textures::TextureEnum;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Copy, Clone)]
 pub enum RoughnessConstants {
@@ -32,33 +34,87 @@ impl RoughnessConstants {
     }
 }
 
+#[derive(Copy, Clone)]
+pub enum RefractiveMedium {
+    Water,
+    Glass,
+    Diamond,
+}
+
+impl RefractiveMedium {
+    const fn value(self) -> f32 {
+        match self {
+            Self::Water => 1.333,
+            Self::Glass => 1.5,
+            Self::Diamond => 2.417,
+        }
+    }
+    const fn string(&self) -> &str {
+        match *self {
+            Self::Water => "Water",
+            Self::Glass => "Glass",
+            Self::Diamond => "Diamond",
+        }
+    }
+}
+
 pub struct MaterialManager<'a> {
     linear_freshnel: HashMap<&'a str, RGBColor>,
     diffuse_colors: HashMap<&'a str, RGBColor>,
     pub materials: HashMap<String, MaterialEnum>,
+    /// Shared between the materials that use them, so large images are only loaded once
+    textures: HashMap<String, Arc<TextureEnum>>,
 }
 
 impl<'a> MaterialManager<'a> {
     const DIFFUSE_REFLECTANCE: f32 = 1.0;
     const SPECULAR_REFLECTANCE: f32 = 1.0;
     const PHONG_EXPONENT: i32 = 60;
+    const ABSORPTION_DENSITY: f32 = 0.5;
 
     pub fn new() -> Self {
         MaterialManager {
             linear_freshnel: create_linear_fresnel_hash_map(),
             diffuse_colors: create_diffuse_rgb_hash_map(),
             materials: HashMap::new(),
+            textures: HashMap::new(),
         }
     }
 
-    pub fn add_material(&mut self, name: String, material: MaterialEnum) {
-        self.materials.entry(name).or_insert(material);
+    /// Keeps the first material added under a name. IDs follow the order the materials were
+    /// added in, so they stay the same between runs.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn add_material(&mut self, name: String, mut material: MaterialEnum) {
+        let id = self.materials.len() as u32 + 1;
+        self.materials.entry(name).or_insert_with(|| {
+            material.set_id(id);
+            material
+        });
     }
 
     pub fn get_material(&self, name: &str) -> Option<&MaterialEnum> {
         self.materials.get(name)
     }
 
+    /// Keeps the first texture added under a name.
+    pub fn add_texture(&mut self, name: String, texture: TextureEnum) {
+        self.textures.entry(name).or_insert_with(|| Arc::new(texture));
+    }
+
+    pub fn get_texture(&self, name: &str) -> Option<Arc<TextureEnum>> {
+        self.textures.get(name).cloned()
+    }
+
+    /// Looks up one of the named diffuse colors, already mapped to the 0-1 range.
+    pub fn get_diffuse_color(&self, color_name: &str) -> Option<RGBColor> {
+        self.diffuse_colors.get(color_name).map(|color| *color / 255.0)
+    }
+
+    /// Looks up the base reflectivity of one of the named metals.
+    pub fn get_linear_fresnel(&self, metal_name: &str) -> Option<RGBColor> {
+        self.linear_freshnel.get(metal_name).copied()
+    }
+
     pub fn add_lambert_material(&mut self, color_name: &str, reflectiveness: i32) {
         let material_id: String = format!("lambert_{color_name}_RE{reflectiveness}");
 
@@ -166,6 +222,33 @@ impl<'a> MaterialManager<'a> {
         self.add_material(material_id, new_material);
     }
 
+    /// The tint color is what survives absorption, so "White" gives perfectly clear glass.
+    pub fn add_dielectric_material(&mut self, tint_color_name: &str, medium: RefractiveMedium) {
+        let material_id: String = format!("dielectric_{}_{}", tint_color_name, medium.string());
+
+        if self.materials.contains_key(&material_id) {
+            return;
+        }
+
+        let tint_color = if let Some(color) = self.diffuse_colors.get(tint_color_name) {
+            *color / 255.0
+        } else {
+            println!("Color {tint_color_name} not found");
+            return;
+        };
+
+        let absorption = (RGBColor::ONE - tint_color) * Self::ABSORPTION_DENSITY;
+        let new_material = MaterialEnum::Dielectric(DielectricMaterial::new(
+            MaterialProperties::new(tint_color, Self::DIFFUSE_REFLECTANCE, 1.0),
+            medium.value(),
+            absorption,
+            Self::SPECULAR_REFLECTANCE,
+            Self::PHONG_EXPONENT,
+        ));
+
+        self.add_material(material_id, new_material);
+    }
+
     const fn map_reflectiveness_input_from_int_to_float(reflectiveness: i32) -> f32 {
         match reflectiveness {
             0 => 0.0,
@@ -1,12 +1,94 @@
 use crate::hitrecord::HitRecord;
+use crate::math::sampling::{self, Sampler};
+use crate::textures::{Texture, TextureEnum};
 use enum_dispatch::enum_dispatch;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
+use std::sync::Arc;
 pub type RGBColor = Vec3;
 
+/// Textures that vary the parameters of a material over its surface. Each one is multiplied with
+/// the constant value of its parameter, roughness and metalness are only used by the Phong BRDF.
+#[derive(Clone)]
+pub struct MaterialTextures {
+    pub albedo: Option<Arc<TextureEnum>>,
+    pub roughness: Option<Arc<TextureEnum>>,
+    /// Replaces the metal flag, 1 is metal and 0 is not
+    pub metalness: Option<Arc<TextureEnum>>,
+    pub reflectiveness: Option<Arc<TextureEnum>>,
+    /// Tangent space normal map, red along u, green along v and blue out of the surface
+    pub normal: Option<Arc<TextureEnum>>,
+    /// Height map, used when there is no normal map
+    pub bump: Option<Arc<TextureEnum>>,
+    /// Scales the slopes of the normal or bump map, 0 leaves the surface flat
+    pub normal_strength: f32,
+}
+
+impl MaterialTextures {
+    /// Step in texture coordinates used to measure the slope of a bump map
+    const BUMP_DELTA: f32 = 0.000_5;
+
+    pub const fn new() -> Self {
+        Self {
+            albedo: None,
+            roughness: None,
+            metalness: None,
+            reflectiveness: None,
+            normal: None,
+            bump: None,
+            normal_strength: 1.0,
+        }
+    }
+
+    /// Replaces the normal of the hit record by the one of the normal or bump map. The result
+    /// is kept on the side of the surface `ray_direction` came from, so shading and the choice
+    /// between entering and leaving an object still agree with the geometry.
+    pub fn perturb_normal(&self, hitrecord: &mut HitRecord<'_>, ray_direction: Vec3) {
+        let local_normal = if let Some(normal_map) = &self.normal {
+            let value = normal_map.sample(hitrecord.uv) * 2.0 - 1.0;
+            (value.truncate() * self.normal_strength).extend(value.z.max(0.0))
+        } else if let Some(bump_map) = &self.bump {
+            let height = bump_map.sample_scalar(hitrecord.uv);
+            let slope = Vec2::new(
+                bump_map.sample_scalar(hitrecord.uv + Vec2::X * Self::BUMP_DELTA) - height,
+                bump_map.sample_scalar(hitrecord.uv + Vec2::Y * Self::BUMP_DELTA) - height,
+            ) / Self::BUMP_DELTA;
+            (-slope * self.normal_strength).extend(1.0)
+        } else {
+            return;
+        };
+
+        let geometric_normal = hitrecord.normal;
+        let mut normal = (hitrecord.tangent * local_normal.x
+            + hitrecord.bitangent * local_normal.y
+            + geometric_normal * local_normal.z)
+            .normalize_or_zero();
+        if normal == Vec3::ZERO {
+            return;
+        }
+
+        let geometric_side = geometric_normal.dot(ray_direction);
+        let side = normal.dot(ray_direction);
+        if side * geometric_side <= 0.0 {
+            // Tilt it back just past the grazing angle
+            normal = (normal - ray_direction * geometric_side.mul_add(-0.01, side)).normalize();
+        }
+        hitrecord.normal = normal;
+    }
+}
+
+impl Default for MaterialTextures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct MaterialProperties {
     pub diffuse_color: RGBColor,
     pub diffuse_reflectance: f32,
     pub reflectiveness_environment: f32,
+    /// Assigned by the material manager, starts at 1 for the first material
+    pub id: u32,
+    pub textures: MaterialTextures,
 }
 
 impl MaterialProperties {
@@ -15,10 +97,36 @@ impl MaterialProperties {
         diffuse_reflectance: f32,
         reflectiveness_environment: f32,
     ) -> Self {
-        Self { diffuse_color, diffuse_reflectance, reflectiveness_environment }
+        Self {
+            diffuse_color,
+            diffuse_reflectance,
+            reflectiveness_environment,
+            id: 0,
+            textures: MaterialTextures::new(),
+        }
+    }
+
+    /// Diffuse color at the texture coordinates of a hitpoint.
+    pub fn get_diffuse_color(&self, uv: Vec2) -> RGBColor {
+        self.textures
+            .albedo
+            .as_ref()
+            .map_or(self.diffuse_color, |texture| self.diffuse_color * texture.sample(uv))
+    }
+
+    pub fn get_reflectiveness_environment(&self, uv: Vec2) -> f32 {
+        self.textures.reflectiveness.as_ref().map_or(self.reflectiveness_environment, |texture| {
+            self.reflectiveness_environment * texture.sample_scalar(uv)
+        })
     }
 }
 
+/// Direction chosen to continue a path, weighted by BRDF * cos(theta) / pdf.
+pub struct BrdfSample {
+    pub direction: Vec3,
+    pub weight: RGBColor,
+}
+
 #[enum_dispatch]
 pub trait Material: Sync + Send {
     fn shade(
@@ -28,7 +136,60 @@ pub trait Material: Sync + Send {
         view_direction: &Vec3,
     ) -> RGBColor;
 
-    fn get_reflectiveness_environment(&self) -> f32;
+    fn get_reflectiveness_environment(&self, hitrecord: &HitRecord<'_>) -> f32;
+
+    /// Color of the surface without any lighting, for the albedo AOV.
+    fn get_albedo(&self, hitrecord: &HitRecord<'_>) -> RGBColor;
+
+    fn get_id(&self) -> u32;
+
+    fn set_id(&mut self, id: u32);
+
+    fn set_textures(&mut self, textures: MaterialTextures);
+
+    /// Bends the normal of the hit record with the normal or bump map of the material.
+    fn perturb_normal(&self, hitrecord: &mut HitRecord<'_>, ray_direction: Vec3);
+
+    /// Index of refraction of materials that let light through, `None` for opaque materials.
+    fn get_index_of_refraction(&self) -> Option<f32> {
+        None
+    }
+
+    /// Beer–Lambert absorption coefficient per unit of distance travelled inside the material.
+    fn get_absorption(&self) -> RGBColor {
+        RGBColor::ZERO
+    }
+
+    /// Picks the next direction of a path traced ray. Cosine weighted sampling is exact for diffuse
+    /// materials, glossy materials sample their specular lobe as well.
+    fn sample_brdf(
+        &self,
+        hitrecord: &HitRecord<'_>,
+        view_direction: &Vec3,
+        sampler: &mut Sampler,
+    ) -> Option<BrdfSample> {
+        let direction = sampling::cosine_sample_hemisphere(hitrecord.normal, sampler.next_vec2());
+        let pdf = sampling::cosine_hemisphere_pdf(hitrecord.normal, direction);
+        weigh_brdf_sample(self, hitrecord, view_direction, direction, pdf)
+    }
+}
+
+/// Evaluates the BRDF for a sampled direction, `None` when it points into the surface or can't
+/// have been sampled.
+pub fn weigh_brdf_sample<M: Material + ?Sized>(
+    material: &M,
+    hitrecord: &HitRecord<'_>,
+    view_direction: &Vec3,
+    direction: Vec3,
+    pdf: f32,
+) -> Option<BrdfSample> {
+    let cos_theta = hitrecord.normal.dot(direction);
+    if cos_theta <= 0.0 || pdf <= 0.0 {
+        return None;
+    }
+
+    let weight = material.shade(hitrecord, &direction, view_direction) * (cos_theta / pdf);
+    Some(BrdfSample { direction, weight })
 }
 
 pub struct DefaultMaterial {}
@@ -43,7 +204,21 @@ impl Material for DefaultMaterial {
         RGBColor::ZERO
     }
 
-    fn get_reflectiveness_environment(&self) -> f32 {
+    fn get_reflectiveness_environment(&self, _hitrecord: &HitRecord<'_>) -> f32 {
         0.0
     }
+
+    fn get_albedo(&self, _hitrecord: &HitRecord<'_>) -> RGBColor {
+        RGBColor::ZERO
+    }
+
+    fn get_id(&self) -> u32 {
+        0
+    }
+
+    fn set_id(&mut self, _id: u32) {}
+
+    fn set_textures(&mut self, _textures: MaterialTextures) {}
+
+    fn perturb_normal(&self, _hitrecord: &mut HitRecord<'_>, _ray_direction: Vec3) {}
 }
@@ -1,3 +1,4 @@
+pub mod dielectric_material;
 pub mod lambert_material;
 pub mod lambert_phong_material;
 pub mod material_definitions;
@@ -6,11 +7,13 @@ pub mod material_properties;
 pub mod phong_brdf_material;
 
 use super::hitrecord::HitRecord;
+use super::math::sampling::Sampler;
+use dielectric_material::DielectricMaterial;
 use enum_dispatch::enum_dispatch;
 use glam::Vec3;
 use lambert_material::LambertMaterial;
 use lambert_phong_material::LambertPhongMaterial;
-use material_properties::Material;
+use material_properties::{BrdfSample, Material, MaterialTextures};
 use phong_brdf_material::PhongBRDFMaterial;
 type RGBColor = Vec3;
 
@@ -19,4 +22,5 @@ pub enum MaterialEnum {
     Lambert(LambertMaterial),
     LambertPhong(LambertPhongMaterial),
     PhongBRDFMaterial(PhongBRDFMaterial),
+    Dielectric(DielectricMaterial),
 }
@@ -1,7 +1,29 @@
 use crate::hitrecord::HitRecord;
-use crate::materials::material_properties::{Material, MaterialProperties, RGBColor};
-use crate::math::brdf;
-use glam::Vec3;
+use crate::materials::material_properties::{
+    weigh_brdf_sample, BrdfSample, Material, MaterialProperties, MaterialTextures, RGBColor,
+};
+use crate::math::sampling::{self, Sampler};
+use crate::math::{brdf, reflect};
+use crate::textures::Texture;
+use glam::{Vec2, Vec3};
+
+const DIELECTRIC_BASE_REFLECTIVITY: RGBColor = RGBColor::splat(0.04);
+/// Keeps textures from making the GGX distribution degenerate
+const MIN_TEXTURED_ROUGHNESS: f32 = 0.01;
+
+/// Parameters of the surface at a hitpoint, after applying the textures of the material.
+struct SurfaceParameters {
+    base_color: RGBColor,
+    roughness: f32,
+    metalness: f32,
+}
+
+impl SurfaceParameters {
+    /// Reflectivity at normal incidence, metals tint their reflections with the base color.
+    fn get_base_reflectivity(&self) -> RGBColor {
+        DIELECTRIC_BASE_REFLECTIVITY.lerp(self.base_color, self.metalness)
+    }
+}
 
 pub struct PhongBRDFMaterial {
     pub properties: MaterialProperties,
@@ -23,36 +45,112 @@ impl PhongBRDFMaterial {
         } else {
             MaterialProperties::new(albedo, diffuse_reflectance, reflectiveness_environment)
         };
-        let albedo = if is_metal { albedo } else { RGBColor::new(0.04, 0.04, 0.04) };
+        let albedo = if is_metal { albedo } else { DIELECTRIC_BASE_REFLECTIVITY };
         Self { properties, albedo, roughness, is_metal }
     }
+
+    /// Metals keep their color in the albedo and dielectrics in the diffuse color, a metalness
+    /// texture blends between both responses using that color.
+    fn get_surface_parameters(&self, uv: Vec2) -> SurfaceParameters {
+        let textures = &self.properties.textures;
+        let base_color = if self.is_metal { self.albedo } else { self.properties.diffuse_color };
+        let base_color =
+            textures.albedo.as_ref().map_or(base_color, |texture| base_color * texture.sample(uv));
+        let roughness = textures.roughness.as_ref().map_or(self.roughness, |texture| {
+            (self.roughness * texture.sample_scalar(uv)).max(MIN_TEXTURED_ROUGHNESS)
+        });
+        let metalness = textures.metalness.as_ref().map_or_else(
+            || f32::from(u8::from(self.is_metal)),
+            |texture| texture.sample_scalar(uv).clamp(0.0, 1.0),
+        );
+        SurfaceParameters { base_color, roughness, metalness }
+    }
 }
 
 impl Material for PhongBRDFMaterial {
-    #[allow(clippy::cast_precision_loss)]
     fn shade(
         &self,
         hitrecord: &HitRecord<'_>,
         light_direction: &Vec3,
         view_direction: &Vec3,
     ) -> RGBColor {
+        let surface = self.get_surface_parameters(hitrecord.uv);
         let half_vector = (*light_direction + *view_direction).normalize();
-        let fresnel: RGBColor = brdf::schlick(&half_vector, view_direction, &self.albedo);
-        let d = brdf::trowbridge_reitz_ggx(&hitrecord.normal, &half_vector, self.roughness);
-        let k = self.roughness.mul_add(self.roughness, 1.0).powi(2) / 8.0;
+        let fresnel: RGBColor =
+            brdf::schlick(&half_vector, view_direction, &surface.get_base_reflectivity());
+        let d = brdf::trowbridge_reitz_ggx(&hitrecord.normal, &half_vector, surface.roughness);
+        let k = surface.roughness.mul_add(surface.roughness, 1.0).powi(2) / 8.0;
         let g = brdf::smith_method(&hitrecord.normal, view_direction, light_direction, k);
-        let kd: RGBColor = (RGBColor::ONE - fresnel) * i32::from(!self.is_metal) as f32;
+        let kd: RGBColor = (RGBColor::ONE - fresnel) * (1.0 - surface.metalness);
 
         let specular = fresnel * d * g
             / (4.0
                 * hitrecord.normal.dot(*light_direction).max(std::f32::EPSILON)
                 * hitrecord.normal.dot(*view_direction).max(std::f32::EPSILON));
 
-        let diffuse = brdf::lambert_color(&self.properties.diffuse_color, &kd);
+        let diffuse = brdf::lambert_color(&surface.base_color, &kd);
         specular + diffuse
     }
 
-    fn get_reflectiveness_environment(&self) -> f32 {
-        self.properties.reflectiveness_environment
+    fn get_reflectiveness_environment(&self, hitrecord: &HitRecord<'_>) -> f32 {
+        self.properties.get_reflectiveness_environment(hitrecord.uv)
+    }
+
+    fn get_albedo(&self, hitrecord: &HitRecord<'_>) -> RGBColor {
+        self.get_surface_parameters(hitrecord.uv).base_color
+    }
+
+    fn get_id(&self) -> u32 {
+        self.properties.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.properties.id = id;
+    }
+
+    fn set_textures(&mut self, textures: MaterialTextures) {
+        self.properties.textures = textures;
+    }
+
+    fn perturb_normal(&self, hitrecord: &mut HitRecord<'_>, ray_direction: Vec3) {
+        self.properties.textures.perturb_normal(hitrecord, ray_direction);
+    }
+
+    fn sample_brdf(
+        &self,
+        hitrecord: &HitRecord<'_>,
+        view_direction: &Vec3,
+        sampler: &mut Sampler,
+    ) -> Option<BrdfSample> {
+        // Metals have no diffuse lobe, dielectrics split their samples evenly between both lobes and
+        // partly metallic texels lie in between
+        let surface = self.get_surface_parameters(hitrecord.uv);
+        let specular_probability = surface.metalness.mul_add(0.5, 0.5);
+
+        let direction = if sampler.next_f32() < specular_probability {
+            let half_vector = sampling::sample_ggx_half_vector(
+                hitrecord.normal,
+                surface.roughness,
+                sampler.next_vec2(),
+            );
+            reflect(-*view_direction, half_vector)
+        } else {
+            sampling::cosine_sample_hemisphere(hitrecord.normal, sampler.next_vec2())
+        };
+
+        let half_vector = (direction + *view_direction).normalize();
+        let distribution =
+            brdf::trowbridge_reitz_ggx(&hitrecord.normal, &half_vector, surface.roughness);
+        let pdf = specular_probability.mul_add(
+            sampling::ggx_reflection_pdf(
+                hitrecord.normal,
+                half_vector,
+                *view_direction,
+                distribution,
+            ),
+            (1.0 - specular_probability)
+                * sampling::cosine_hemisphere_pdf(hitrecord.normal, direction),
+        );
+        weigh_brdf_sample(self, hitrecord, view_direction, direction, pdf)
     }
 }
@@ -0,0 +1,93 @@
+use crate::ray::Ray;
+use glam::Vec3;
+
+/// Axis-aligned bounding box, stored as its minimum and maximum corner.
+/// An empty box has `min > max` on every axis so that growing it by any point yields that point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl Aabb {
+    pub const EMPTY: Self = Self { min: Vec3::MAX, max: Vec3::MIN };
+
+    pub const fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: &[Vec3]) -> Self {
+        points.iter().fold(Self::EMPTY, |mut aabb, point| {
+            aabb.grow(*point);
+            aabb
+        })
+    }
+
+    pub fn grow(&mut self, point: Vec3) {
+        self.min = self.min.min(point);
+        self.max = self.max.max(point);
+    }
+
+    pub fn grow_aabb(&mut self, other: &Self) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn extent(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let extent = self.extent();
+        2.0 * extent.z.mul_add(extent.x, extent.x.mul_add(extent.y, extent.y * extent.z))
+    }
+
+    /// Returns 0, 1 or 2 for the x, y or z axis along which the box is the widest.
+    pub fn largest_axis(&self) -> usize {
+        let extent = self.extent();
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Slab test. Returns the distance at which the ray enters the box, clamped to `t_min`,
+    /// or `None` when the box is missed within the ray's interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `inv_direction` - the component-wise reciprocal of the ray direction, precomputed by the caller.
+    pub fn hit(&self, ray: &Ray, inv_direction: Vec3) -> Option<f32> {
+        let t0 = (self.min - ray.origin) * inv_direction;
+        let t1 = (self.max - ray.origin) * inv_direction;
+
+        let t_enter = t0.min(t1).max_element().max(ray.t_min);
+        let t_exit = t0.max(t1).min_element().min(ray.t_max);
+
+        (t_enter <= t_exit).then_some(t_enter)
+    }
+}
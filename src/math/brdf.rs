@@ -56,3 +56,22 @@ pub fn schlick_ggx(half_vector: &Vec3, view_dir: &Vec3, k: f32) -> f32 {
 pub fn smith_method(normal_surface: &Vec3, view_dir: &Vec3, light_dir: &Vec3, roughness: f32) -> f32 {
     schlick_ggx(normal_surface, view_dir, roughness) * schlick_ggx(normal_surface, light_dir, roughness)
 }
+
+/// Exact Fresnel reflectance for unpolarized light at the boundary between two dielectrics.
+/// Returns 1.0 on total internal reflection.
+///
+/// # Arguments
+///
+/// * `cos_incident` - cosine of the angle between the incoming direction and the surface normal on the incident side.
+/// * `eta` - ratio of the index of refraction on the incident side over the one on the transmitted side.
+pub fn fresnel_dielectric(cos_incident: f32, eta: f32) -> f32 {
+    let sin_transmitted_sq = eta * eta * cos_incident.mul_add(-cos_incident, 1.0).max(0.0);
+    if sin_transmitted_sq >= 1.0 {
+        return 1.0;
+    }
+
+    let cos_transmitted = (1.0 - sin_transmitted_sq).sqrt();
+    let r_perpendicular = eta.mul_add(cos_incident, -cos_transmitted) / eta.mul_add(cos_incident, cos_transmitted);
+    let r_parallel = eta.mul_add(-cos_transmitted, cos_incident) / eta.mul_add(cos_transmitted, cos_incident);
+    r_perpendicular.mul_add(r_perpendicular, r_parallel * r_parallel) * 0.5
+}
@@ -1,10 +1,17 @@
+pub mod aabb;
 pub mod brdf;
 mod evector;
+pub mod noise;
+pub mod pixel_filter;
+pub mod sampling;
+pub mod tone_mapping;
 
-use glam::Vec3;
+use glam::{Quat, Vec3};
 
 pub trait ColorTypeFunctionality {
     fn max_to_one(&mut self);
+    /// Perceived brightness of a linear Rec. 709 color.
+    fn luminance(&self) -> f32;
 }
 
 impl ColorTypeFunctionality for Vec3 {
@@ -14,4 +21,36 @@ impl ColorTypeFunctionality for Vec3 {
             *self /= max;
         }
     }
+
+    fn luminance(&self) -> f32 {
+        self.dot(Self::new(0.2126, 0.7152, 0.0722))
+    }
+}
+
+/// Rotation by `rotation.x` degrees around the x axis, followed by the y and then the z axis.
+pub fn rotation_from_euler_degrees(rotation: Vec3) -> Quat {
+    let [x, y, z] = rotation.to_array().map(f32::to_radians);
+    Quat::from_rotation_z(z) * Quat::from_rotation_y(y) * Quat::from_rotation_x(x)
+}
+
+/// Mirrors `direction` around `normal`.
+pub fn reflect(direction: Vec3, normal: Vec3) -> Vec3 {
+    direction - normal * (direction.dot(normal) * 2.0)
+}
+
+/// Bends `direction` through a surface following Snell's law, `None` on total internal reflection.
+///
+/// # Arguments
+///
+/// * `normal` - the surface normal on the side `direction` comes from.
+/// * `eta` - ratio of the index of refraction on the incident side over the one on the transmitted side.
+pub fn refract(direction: Vec3, normal: Vec3, eta: f32) -> Option<Vec3> {
+    let cos_incident = -direction.dot(normal);
+    let sin_transmitted_sq = eta * eta * cos_incident.mul_add(-cos_incident, 1.0);
+    if sin_transmitted_sq > 1.0 {
+        return None;
+    }
+
+    let cos_transmitted = (1.0 - sin_transmitted_sq).sqrt();
+    Some((direction * eta + normal * eta.mul_add(cos_incident, -cos_transmitted)).normalize())
 }
@@ -0,0 +1,90 @@
+use glam::{IVec2, Vec2};
+
+/// Hashes a lattice point and a seed into 32 well mixed bits.
+#[allow(clippy::cast_sign_loss)]
+const fn hash(cell: IVec2, seed: u32) -> u32 {
+    let mut hash = (cell.x as u32).wrapping_mul(0x8DA6_B343)
+        ^ (cell.y as u32).wrapping_mul(0xD816_3841)
+        ^ seed.wrapping_mul(0xCB1A_B31F);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7FEB_352D);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846C_A68B);
+    hash ^ (hash >> 16)
+}
+
+/// Uniform value in [0, 1) for a lattice point.
+#[allow(clippy::cast_precision_loss)]
+fn hash_to_unit(cell: IVec2, seed: u32) -> f32 {
+    (hash(cell, seed) >> 8) as f32 / (1 << 24) as f32
+}
+
+/// Quintic curve that makes the interpolation between lattice points smooth up to the second
+/// derivative.
+fn fade(t: Vec2) -> Vec2 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(start: f32, end: f32, t: f32) -> f32 {
+    (end - start).mul_add(t, start)
+}
+
+/// Gradient noise by Ken Perlin, roughly in [-1, 1] and 0 on every lattice point.
+#[allow(clippy::cast_possible_truncation)]
+pub fn perlin(position: Vec2, seed: u32) -> f32 {
+    let cell = position.floor();
+    let local = position - cell;
+    let cell = cell.as_ivec2();
+
+    let gradient_dot = |offset: IVec2| {
+        let angle = hash_to_unit(cell + offset, seed) * std::f32::consts::TAU;
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(cos, sin).dot(local - offset.as_vec2())
+    };
+
+    let weight = fade(local);
+    let bottom = lerp(gradient_dot(IVec2::ZERO), gradient_dot(IVec2::X), weight.x);
+    let top = lerp(gradient_dot(IVec2::Y), gradient_dot(IVec2::ONE), weight.x);
+    // Random unit gradients reach at most 1/√2
+    lerp(bottom, top, weight.y) * std::f32::consts::SQRT_2
+}
+
+/// Cellular noise by Steven Worley: distance to the nearest of one random feature point per cell,
+/// 0 on the points and about 1 at most.
+pub fn worley(position: Vec2, seed: u32) -> f32 {
+    let cell = position.floor();
+    let local = position - cell;
+    let cell = cell.as_ivec2();
+
+    let mut nearest_distance_squared = f32::MAX;
+    for y in -1..=1 {
+        for x in -1..=1 {
+            let offset = IVec2::new(x, y);
+            let feature_point = offset.as_vec2()
+                + Vec2::new(
+                    hash_to_unit(cell + offset, seed),
+                    hash_to_unit(cell + offset, seed.wrapping_add(1)),
+                );
+            nearest_distance_squared =
+                nearest_distance_squared.min(feature_point.distance_squared(local));
+        }
+    }
+
+    nearest_distance_squared.sqrt().min(1.0)
+}
+
+/// Sums `octaves` layers of `noise`, every layer twice as fine and half as strong as the previous
+/// one. The result is normalized so it stays in the range of a single layer.
+pub fn fractal(noise: fn(Vec2, u32) -> f32, position: Vec2, octaves: u32, seed: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut total_amplitude = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    for octave in 0..octaves.max(1) {
+        sum += noise(position * frequency, seed.wrapping_add(octave * 2)) * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total_amplitude
+}
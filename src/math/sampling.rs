@@ -0,0 +1,110 @@
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+/// Small PCG32 random number generator, every pixel and sample gets its own deterministic stream
+/// so renders are reproducible and the rayon threads never share state.
+#[derive(Clone)]
+pub struct Sampler {
+    state: u64,
+}
+
+impl Sampler {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+    const INCREMENT: u64 = 1_442_695_040_888_963_407;
+
+    pub fn new(pixel_index: u32, sample_index: u32) -> Self {
+        // SplitMix64 spreads neighbouring seeds over the whole state space
+        let mut seed = (u64::from(pixel_index) << 32 | u64::from(sample_index))
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        seed ^= seed >> 31;
+
+        let mut sampler = Self { state: seed.wrapping_add(Self::INCREMENT) };
+        sampler.next_u32();
+        sampler
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn next_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = old_state.wrapping_mul(Self::MULTIPLIER).wrapping_add(Self::INCREMENT);
+        let xor_shifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        xor_shifted.rotate_right((old_state >> 59) as u32)
+    }
+
+    /// Uniform float in [0, 1)
+    #[allow(clippy::cast_precision_loss)]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn next_vec2(&mut self) -> Vec2 {
+        Vec2::new(self.next_f32(), self.next_f32())
+    }
+}
+
+/// Two tangents that form a right handed basis with `normal` (Duff et al. 2017).
+pub fn orthonormal_basis(normal: Vec3) -> (Vec3, Vec3) {
+    let sign = 1.0f32.copysign(normal.z);
+    let a = -1.0 / (sign + normal.z);
+    let b = normal.x * normal.y * a;
+    let tangent =
+        Vec3::new((sign * normal.x * normal.x).mul_add(a, 1.0), sign * b, -sign * normal.x);
+    let bitangent = Vec3::new(b, normal.y.mul_add(normal.y * a, sign), -normal.y);
+    (tangent, bitangent)
+}
+
+/// Turns spherical coordinates around `axis` into a world space direction.
+pub fn direction_around_axis(axis: Vec3, cos_theta: f32, phi: f32) -> Vec3 {
+    let (tangent, bitangent) = orthonormal_basis(axis);
+    let sin_theta = cos_theta.mul_add(-cos_theta, 1.0).max(0.0).sqrt();
+    (tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + axis * cos_theta)
+        .normalize()
+}
+
+/// Point distributed uniformly on the unit disk.
+pub fn uniform_sample_disk(random: Vec2) -> Vec2 {
+    let angle = 2.0 * PI * random.y;
+    Vec2::new(angle.cos(), angle.sin()) * random.x.sqrt()
+}
+
+/// Direction in the hemisphere around `normal` with a probability density of cos(theta) / PI.
+pub fn cosine_sample_hemisphere(normal: Vec3, random: Vec2) -> Vec3 {
+    direction_around_axis(normal, (1.0 - random.x).sqrt(), 2.0 * PI * random.y)
+}
+
+pub fn cosine_hemisphere_pdf(normal: Vec3, direction: Vec3) -> f32 {
+    normal.dot(direction).max(0.0) / PI
+}
+
+/// Direction distributed around `axis` proportionally to cos(alpha)^exponent.
+#[allow(clippy::cast_precision_loss)]
+pub fn sample_phong_lobe(axis: Vec3, exponent: i32, random: Vec2) -> Vec3 {
+    direction_around_axis(axis, random.x.powf(1.0 / (exponent as f32 + 1.0)), 2.0 * PI * random.y)
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn phong_lobe_pdf(axis: Vec3, exponent: i32, direction: Vec3) -> f32 {
+    let cos_alpha = axis.dot(direction).max(0.0);
+    (exponent as f32 + 1.0) / (2.0 * PI) * cos_alpha.powi(exponent)
+}
+
+/// Microfacet normal distributed proportionally to the Trowbridge-Reitz GGX distribution times
+/// cos(theta), using the same roughness remapping as `brdf::trowbridge_reitz_ggx`.
+pub fn sample_ggx_half_vector(normal: Vec3, roughness: f32, random: Vec2) -> Vec3 {
+    let alpha_sq = roughness.powi(4);
+    let cos_theta_sq = (1.0 - random.x) / random.x.mul_add(alpha_sq - 1.0, 1.0);
+    direction_around_axis(normal, cos_theta_sq.sqrt(), 2.0 * PI * random.y)
+}
+
+/// Density of a direction reflected around a half vector drawn by `sample_ggx_half_vector`.
+pub fn ggx_reflection_pdf(
+    normal: Vec3,
+    half_vector: Vec3,
+    view_direction: Vec3,
+    distribution: f32,
+) -> f32 {
+    distribution * normal.dot(half_vector).max(0.0)
+        / (4.0 * view_direction.dot(half_vector).max(f32::EPSILON))
+}
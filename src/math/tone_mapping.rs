@@ -0,0 +1,165 @@
+use super::ColorTypeFunctionality;
+use glam::{Mat3, Vec3};
+use std::{fmt, str::FromStr};
+
+pub type RGBColor = Vec3;
+
+/// Operator that squeezes the unbounded linear radiance of the renderer into the [0, 1] range of
+/// the display. Every operator returns linear values, the sRGB encoding happens afterwards.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToneMapper {
+    /// Divides by the largest channel when it exceeds 1, keeps the hue but nothing else
+    Normalize,
+    Reinhard,
+    /// Filmic curve fitted to the ACES reference rendering transform by Krzysztof Narkowicz
+    Aces,
+    /// Filmic curve John Hable made for Uncharted 2
+    Uncharted2,
+    /// Minimal `AgX` approximation by Benjamin Wrensch, desaturates bright colors towards white
+    Agx,
+}
+
+impl ToneMapper {
+    pub fn apply(self, color: RGBColor) -> RGBColor {
+        let color = color.max(RGBColor::ZERO);
+        match self {
+            Self::Normalize => {
+                let mut color = color;
+                color.max_to_one();
+                color
+            }
+            Self::Reinhard => color / (color + RGBColor::ONE),
+            Self::Aces => aces(color),
+            Self::Uncharted2 => uncharted2(color),
+            Self::Agx => agx(color),
+        }
+        .clamp(RGBColor::ZERO, RGBColor::ONE)
+    }
+
+    /// The next operator, for cycling through them at runtime.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Normalize => Self::Reinhard,
+            Self::Reinhard => Self::Aces,
+            Self::Aces => Self::Uncharted2,
+            Self::Uncharted2 => Self::Agx,
+            Self::Agx => Self::Normalize,
+        }
+    }
+}
+
+impl fmt::Display for ToneMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Normalize => "normalize",
+            Self::Reinhard => "reinhard",
+            Self::Aces => "aces",
+            Self::Uncharted2 => "uncharted2",
+            Self::Agx => "agx",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for ToneMapper {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "normalize" => Ok(Self::Normalize),
+            "reinhard" => Ok(Self::Reinhard),
+            "aces" => Ok(Self::Aces),
+            "uncharted2" => Ok(Self::Uncharted2),
+            "agx" => Ok(Self::Agx),
+            _ => Err(format!(
+                "unknown tone mapper '{name}', use normalize, reinhard, aces, uncharted2 or agx"
+            )),
+        }
+    }
+}
+
+/// Encodes a linear color channel in [0, 1] with the sRGB transfer function.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        value.powf(1.0 / 2.4).mul_add(1.055, -0.055)
+    }
+}
+
+/// Decodes an sRGB encoded color channel in [0, 1] back to linear.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn aces(color: RGBColor) -> RGBColor {
+    let numerator = color * (color * 2.51 + 0.03);
+    let denominator = color * (color * 2.43 + 0.59) + 0.14;
+    numerator / denominator
+}
+
+fn uncharted2(color: RGBColor) -> RGBColor {
+    const EXPOSURE_BIAS: f32 = 2.0;
+    const WHITE_POINT: f32 = 11.2;
+
+    let curve = |x: RGBColor| {
+        const SHOULDER_STRENGTH: f32 = 0.15;
+        const LINEAR_STRENGTH: f32 = 0.5;
+        const LINEAR_ANGLE: f32 = 0.1;
+        const TOE_STRENGTH: f32 = 0.2;
+        const TOE_NUMERATOR: f32 = 0.02;
+        const TOE_DENOMINATOR: f32 = 0.3;
+
+        let numerator = x * (x * SHOULDER_STRENGTH + LINEAR_ANGLE * LINEAR_STRENGTH)
+            + TOE_STRENGTH * TOE_NUMERATOR;
+        let denominator =
+            x * (x * SHOULDER_STRENGTH + LINEAR_STRENGTH) + TOE_STRENGTH * TOE_DENOMINATOR;
+        numerator / denominator - TOE_NUMERATOR / TOE_DENOMINATOR
+    };
+
+    curve(color * EXPOSURE_BIAS) / curve(RGBColor::splat(WHITE_POINT))
+}
+
+fn agx(color: RGBColor) -> RGBColor {
+    const MIN_EV: f32 = -12.473_931;
+    const MAX_EV: f32 = 4.026_069;
+    // From the highest to the lowest power
+    const CONTRAST_CURVE: [f32; 7] = [15.5, -40.14, 31.96, -6.868, 0.4298, 0.1191, -0.002_32];
+    const INSET: Mat3 = Mat3::from_cols_array(&[
+        0.842_479_06,
+        0.042_328_24,
+        0.042_375_654,
+        0.078_433_6,
+        0.878_468_6,
+        0.078_433_6,
+        0.079_223_745,
+        0.079_166_13,
+        0.879_143,
+    ]);
+    const OUTSET: Mat3 = Mat3::from_cols_array(&[
+        1.196_879,
+        -0.052_896_85,
+        -0.052_971_635,
+        -0.098_020_88,
+        1.151_903_1,
+        -0.098_043_45,
+        -0.099_029_74,
+        -0.098_961_18,
+        1.151_073_7,
+    ]);
+
+    let log_color =
+        RGBColor::from_array((INSET * color).to_array().map(|x| x.max(f32::MIN_POSITIVE).log2()));
+    let normalized = (log_color.clamp(RGBColor::splat(MIN_EV), RGBColor::splat(MAX_EV)) - MIN_EV)
+        / (MAX_EV - MIN_EV);
+    // Polynomial fit of the default AgX contrast curve, its output is display encoded
+    let display = normalized.to_array().map(|x| {
+        CONTRAST_CURVE.iter().fold(0.0f32, |result, coefficient| result.mul_add(x, *coefficient))
+    });
+
+    (OUTSET * RGBColor::from_array(display)).max(RGBColor::ZERO).powf(2.2)
+}
@@ -0,0 +1,312 @@
+use crate::math::aabb::Aabb;
+use crate::ray::Ray;
+use glam::Vec3;
+
+const BIN_COUNT: usize = 12;
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Copy)]
+struct BvhNode {
+    bounds: Aabb,
+    // index of the left child for inner nodes (the right child is always stored right after it),
+    // index into `primitive_indices` of the first primitive for leaf nodes
+    left_or_first: u32,
+    primitive_count: u32,
+}
+
+impl BvhNode {
+    const fn is_leaf(&self) -> bool {
+        self.primitive_count > 0
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    bounds: Aabb,
+    primitive_count: u32,
+}
+
+/// Bounding volume hierarchy over an arbitrary set of primitives, built with a binned
+/// surface-area heuristic. The hierarchy only knows the bounds of the primitives; intersecting
+/// an actual primitive is left to the caller through the closure passed to [`Bvh::hit`].
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    primitive_indices: Vec<u32>,
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_precision_loss)]
+#[allow(clippy::cast_sign_loss)]
+impl Bvh {
+    pub const fn empty() -> Self {
+        Self { nodes: Vec::new(), primitive_indices: Vec::new() }
+    }
+
+    pub fn new(primitive_bounds: &[Aabb]) -> Self {
+        let primitive_count = primitive_bounds.len();
+        let mut bvh = Self {
+            nodes: Vec::with_capacity((primitive_count * 2).saturating_sub(1)),
+            primitive_indices: (0..primitive_count as u32).collect(),
+        };
+
+        if primitive_count == 0 {
+            return bvh;
+        }
+
+        let centroids: Vec<Vec3> = primitive_bounds.iter().map(Aabb::centroid).collect();
+
+        bvh.nodes.push(BvhNode {
+            bounds: Aabb::EMPTY,
+            left_or_first: 0,
+            primitive_count: primitive_count as u32,
+        });
+        bvh.update_node_bounds(0, primitive_bounds);
+        bvh.subdivide(0, 0, primitive_bounds, &centroids);
+        bvh
+    }
+
+    /// Recomputes the bounds of every node bottom-up without changing the tree topology.
+    /// Cheaper than a rebuild when primitives moved but were not added or removed,
+    /// at the cost of a gradually worse tree when they move a lot.
+    pub fn refit(&mut self, primitive_bounds: &[Aabb]) {
+        // Children are always stored after their parent, so walking backwards visits them first
+        for node_index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[node_index];
+            if node.is_leaf() {
+                self.update_node_bounds(node_index, primitive_bounds);
+            } else {
+                let left_index = node.left_or_first as usize;
+                self.nodes[node_index].bounds =
+                    self.nodes[left_index].bounds.union(&self.nodes[left_index + 1].bounds);
+            }
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, |root| root.bounds)
+    }
+
+    /// Walks the hierarchy front to back and calls `hit_primitive` for every primitive whose
+    /// leaf the ray reaches. The closure returns the hit distance when the primitive is hit,
+    /// which is then used to shrink the ray so only closer primitives are tested afterwards.
+    /// Shadow rays stop at the first hit.
+    pub fn hit<F>(&self, ray: &Ray, is_shadow_ray: bool, mut hit_primitive: F) -> bool
+    where
+        F: FnMut(usize, &Ray) -> Option<f32>,
+    {
+        let Some(root) = self.nodes.first() else {
+            return false;
+        };
+
+        let mut ray = *ray;
+        let inv_direction = ray.direction.recip();
+
+        let Some(root_distance) = root.bounds.hit(&ray, inv_direction) else {
+            return false;
+        };
+
+        let mut stack = [(0u32, 0.0f32); MAX_DEPTH];
+        stack[0] = (0, root_distance);
+        let mut stack_size = 1;
+        let mut hit_anything = false;
+
+        while stack_size > 0 {
+            stack_size -= 1;
+            let (node_index, entry_distance) = stack[stack_size];
+
+            // A closer hit was found after this node was pushed
+            if entry_distance > ray.t_max {
+                continue;
+            }
+
+            let node = &self.nodes[node_index as usize];
+
+            if node.is_leaf() {
+                let first = node.left_or_first as usize;
+                let last = first + node.primitive_count as usize;
+
+                for &primitive_index in &self.primitive_indices[first..last] {
+                    if let Some(t) = hit_primitive(primitive_index as usize, &ray) {
+                        if is_shadow_ray {
+                            return true;
+                        }
+                        ray.t_max = t;
+                        hit_anything = true;
+                    }
+                }
+                continue;
+            }
+
+            let left_index = node.left_or_first;
+            let right_index = left_index + 1;
+            let left_distance = self.nodes[left_index as usize].bounds.hit(&ray, inv_direction);
+            let right_distance = self.nodes[right_index as usize].bounds.hit(&ray, inv_direction);
+
+            // Push the far child first so the near child is popped and visited first
+            match (left_distance, right_distance) {
+                (Some(left), Some(right)) => {
+                    let (near, far) = if left <= right {
+                        ((left_index, left), (right_index, right))
+                    } else {
+                        ((right_index, right), (left_index, left))
+                    };
+                    stack[stack_size] = far;
+                    stack[stack_size + 1] = near;
+                    stack_size += 2;
+                }
+                (Some(left), None) => {
+                    stack[stack_size] = (left_index, left);
+                    stack_size += 1;
+                }
+                (None, Some(right)) => {
+                    stack[stack_size] = (right_index, right);
+                    stack_size += 1;
+                }
+                (None, None) => {}
+            }
+        }
+
+        hit_anything
+    }
+
+    fn update_node_bounds(&mut self, node_index: usize, primitive_bounds: &[Aabb]) {
+        let node = &mut self.nodes[node_index];
+        let first = node.left_or_first as usize;
+        let last = first + node.primitive_count as usize;
+
+        node.bounds = self.primitive_indices[first..last]
+            .iter()
+            .fold(Aabb::EMPTY, |bounds, &index| bounds.union(&primitive_bounds[index as usize]));
+    }
+
+    fn subdivide(
+        &mut self,
+        node_index: usize,
+        depth: usize,
+        primitive_bounds: &[Aabb],
+        centroids: &[Vec3],
+    ) {
+        // Every level can push one extra entry on the traversal stack
+        if depth + 2 >= MAX_DEPTH {
+            return;
+        }
+
+        let node = self.nodes[node_index];
+        let Some((axis, split_position, split_cost)) =
+            self.find_best_split(&node, primitive_bounds, centroids)
+        else {
+            return;
+        };
+
+        let leaf_cost = node.primitive_count as f32 * node.bounds.surface_area();
+        if split_cost >= leaf_cost {
+            return;
+        }
+
+        // Partition the primitive indices in place around the split position
+        let first = node.left_or_first as usize;
+        let last = first + node.primitive_count as usize;
+        let mut left_end = first;
+        for i in first..last {
+            if centroids[self.primitive_indices[i] as usize][axis] < split_position {
+                self.primitive_indices.swap(i, left_end);
+                left_end += 1;
+            }
+        }
+
+        let left_count = (left_end - first) as u32;
+        if left_count == 0 || left_count == node.primitive_count {
+            return;
+        }
+
+        let left_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds: Aabb::EMPTY,
+            left_or_first: first as u32,
+            primitive_count: left_count,
+        });
+        self.nodes.push(BvhNode {
+            bounds: Aabb::EMPTY,
+            left_or_first: left_end as u32,
+            primitive_count: node.primitive_count - left_count,
+        });
+
+        self.nodes[node_index].left_or_first = left_index as u32;
+        self.nodes[node_index].primitive_count = 0;
+
+        self.update_node_bounds(left_index, primitive_bounds);
+        self.update_node_bounds(left_index + 1, primitive_bounds);
+        self.subdivide(left_index, depth + 1, primitive_bounds, centroids);
+        self.subdivide(left_index + 1, depth + 1, primitive_bounds, centroids);
+    }
+
+    /// Bins the primitive centroids along every axis and returns the axis, split position and
+    /// surface area heuristic cost of the cheapest split between two bins.
+    #[allow(clippy::needless_range_loop)]
+    fn find_best_split(
+        &self,
+        node: &BvhNode,
+        primitive_bounds: &[Aabb],
+        centroids: &[Vec3],
+    ) -> Option<(usize, f32, f32)> {
+        let first = node.left_or_first as usize;
+        let primitives = &self.primitive_indices[first..first + node.primitive_count as usize];
+
+        let centroid_bounds = primitives.iter().fold(Aabb::EMPTY, |mut bounds, &index| {
+            bounds.grow(centroids[index as usize]);
+            bounds
+        });
+
+        let mut best_split: Option<(usize, f32, f32)> = None;
+
+        for axis in 0..3 {
+            let axis_min = centroid_bounds.min[axis];
+            let axis_extent = centroid_bounds.max[axis] - axis_min;
+            if axis_extent <= 0.0 {
+                continue;
+            }
+
+            let mut bins = [Bin { bounds: Aabb::EMPTY, primitive_count: 0 }; BIN_COUNT];
+            let scale = BIN_COUNT as f32 / axis_extent;
+
+            for &index in primitives {
+                let bin_index = (((centroids[index as usize][axis] - axis_min) * scale) as usize)
+                    .min(BIN_COUNT - 1);
+                bins[bin_index].bounds.grow_aabb(&primitive_bounds[index as usize]);
+                bins[bin_index].primitive_count += 1;
+            }
+
+            // Sweep from both sides to get the cost of every plane in between two bins
+            let mut left_counts = [0u32; BIN_COUNT - 1];
+            let mut left_costs = [0.0f32; BIN_COUNT - 1];
+            let mut left_bounds = Aabb::EMPTY;
+            let mut left_count = 0;
+            for i in 0..BIN_COUNT - 1 {
+                left_bounds.grow_aabb(&bins[i].bounds);
+                left_count += bins[i].primitive_count;
+                left_counts[i] = left_count;
+                left_costs[i] = left_count as f32 * left_bounds.surface_area();
+            }
+
+            let mut right_bounds = Aabb::EMPTY;
+            let mut right_count = 0;
+            for i in (1..BIN_COUNT).rev() {
+                right_bounds.grow_aabb(&bins[i].bounds);
+                right_count += bins[i].primitive_count;
+
+                if left_counts[i - 1] == 0 || right_count == 0 {
+                    continue;
+                }
+
+                let cost =
+                    (right_count as f32).mul_add(right_bounds.surface_area(), left_costs[i - 1]);
+                if best_split.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    let split_position = axis_min + axis_extent * i as f32 / BIN_COUNT as f32;
+                    best_split = Some((axis, split_position, cost));
+                }
+            }
+        }
+
+        best_split
+    }
+}
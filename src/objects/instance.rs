@@ -0,0 +1,116 @@
+use super::object_properties::Object;
+use super::ObjectEnum;
+use crate::hitrecord::HitRecord;
+use crate::materials::MaterialEnum;
+use crate::math::aabb::Aabb;
+use crate::ray::Ray;
+use glam::{Affine3A, Mat3A, Vec3};
+use std::sync::Arc;
+
+/// Places shared geometry, such as a loaded triangle mesh, in the scene with an affine transform.
+/// Rays are moved into the object space of the geometry instead of transforming the geometry, so
+/// any number of instances can share the same triangles.
+pub struct Instance<'mm> {
+    pub geometry: Arc<ObjectEnum<'mm>>,
+    /// Replaces the materials of the geometry when set
+    pub material: Option<&'mm MaterialEnum>,
+    /// From object space to world space
+    transform: Affine3A,
+    inverse_transform: Affine3A,
+    /// Inverse transpose of the linear part, keeps normals perpendicular under non-uniform scale
+    normal_matrix: Mat3A,
+}
+
+impl<'mm> Instance<'mm> {
+    /// # Panics
+    ///
+    /// When `transform` can't be inverted, for example because it scales an axis to 0.
+    pub fn new(
+        geometry: Arc<ObjectEnum<'mm>>,
+        transform: Affine3A,
+        material: Option<&'mm MaterialEnum>,
+    ) -> Self {
+        assert!(is_invertible(&transform), "instance transform can't be inverted: {transform:?}");
+        let inverse_transform = transform.inverse();
+        Self {
+            geometry,
+            material,
+            transform,
+            inverse_transform,
+            normal_matrix: inverse_transform.matrix3.transpose(),
+        }
+    }
+
+    pub const fn get_transform(&self) -> &Affine3A {
+        &self.transform
+    }
+
+    /// Keeps the previous transform when `transform` can't be inverted, so an animation that
+    /// passes through a scale of 0 leaves the instance where it was instead of breaking it.
+    pub fn set_transform(&mut self, transform: Affine3A) {
+        if !is_invertible(&transform) {
+            return;
+        }
+        self.transform = transform;
+        self.inverse_transform = transform.inverse();
+        self.normal_matrix = self.inverse_transform.matrix3.transpose();
+    }
+}
+
+// A determinant of 0 flattens the geometry onto a plane, a line or a point, its inverse is NaN
+fn is_invertible(transform: &Affine3A) -> bool {
+    transform.is_finite() && transform.matrix3.determinant().is_normal()
+}
+
+impl<'mm> Object<'mm> for Instance<'mm> {
+    fn hit(&self, ray: &Ray, hit_record: &mut HitRecord<'mm>, is_shadow_ray: bool) -> bool {
+        // The objects expect unit directions, so distances are scaled along with the direction
+        let direction = self.inverse_transform.transform_vector3(ray.direction);
+        let length = direction.length();
+        if length < f32::EPSILON {
+            return false;
+        }
+
+        let object_ray = Ray::new_with_limits(
+            self.inverse_transform.transform_point3(ray.origin),
+            direction / length,
+            ray.t_min * length,
+            ray.t_max * length,
+        );
+
+        if !self.geometry.hit(&object_ray, hit_record, is_shadow_ray) {
+            return false;
+        }
+
+        if is_shadow_ray {
+            return true;
+        }
+
+        hit_record.t /= length;
+        hit_record.hitpoint = self.transform.transform_point3(hit_record.hitpoint);
+        hit_record.normal = self.normal_matrix.mul_vec3(hit_record.normal).normalize();
+        hit_record.set_tangent_frame(
+            self.transform.transform_vector3(hit_record.tangent),
+            self.transform.transform_vector3(hit_record.bitangent),
+        );
+        if self.material.is_some() {
+            hit_record.material = self.material;
+        }
+
+        true
+    }
+
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn get_bounding_box(&self) -> Option<Aabb> {
+        let bounds = self.geometry.get_bounding_box()?;
+        let corners: [Vec3; 8] = std::array::from_fn(|corner| {
+            self.transform.transform_point3(Vec3::new(
+                if corner & 1 == 0 { bounds.min.x } else { bounds.max.x },
+                if corner & 2 == 0 { bounds.min.y } else { bounds.max.y },
+                if corner & 4 == 0 { bounds.min.z } else { bounds.max.z },
+            ))
+        });
+        Some(Aabb::from_points(&corners))
+    }
+}
@@ -1,11 +1,14 @@
 use enum_dispatch::enum_dispatch;
 
 use self::{
-    object_properties::Object, plane::Plane, sphere::Sphere, triangle::Triangle,
-    triangle_mesh::TriangleMesh,
+    instance::Instance, object_properties::Object, plane::Plane, sphere::Sphere,
+    triangle::Triangle, triangle_mesh::TriangleMesh,
 };
-use crate::{hitrecord::HitRecord, ray::Ray};
+use crate::{hitrecord::HitRecord, math::aabb::Aabb, ray::Ray};
 
+pub mod bvh;
+pub mod instance;
+pub mod obj_file;
 pub mod object_properties;
 pub mod plane;
 pub mod sphere;
@@ -18,4 +21,5 @@ pub enum ObjectEnum<'mm> {
     Sphere(Sphere<'mm>),
     Triangle(Triangle<'mm>),
     TriangleMesh(TriangleMesh<'mm>),
+    Instance(Instance<'mm>),
 }
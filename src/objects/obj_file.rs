@@ -0,0 +1,515 @@
+use glam::{Vec2, Vec3};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+/// Error raised while loading an OBJ file, pointing at the offending line when it is known.
+#[derive(Debug)]
+pub struct ObjError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// A unique combination of position, texture coordinate and normal referenced by the faces.
+#[derive(Clone, Copy)]
+pub struct ObjVertex {
+    pub position: Vec3,
+    pub normal: Option<Vec3>,
+    pub texture_coordinate: Option<Vec2>,
+}
+
+/// A run of consecutive triangles that share the same group name and material.
+pub struct ObjGroup {
+    pub name: String,
+    pub material: Option<String>,
+    pub first_triangle: usize,
+    pub triangle_count: usize,
+}
+
+pub struct ObjFile {
+    pub vertices: Vec<ObjVertex>,
+    /// Three vertex indices per triangle, in the winding order of the file
+    pub indices: Vec<u32>,
+    pub groups: Vec<ObjGroup>,
+    /// Material libraries referenced with mtllib, resolved relative to the OBJ file
+    pub material_libraries: Vec<PathBuf>,
+}
+
+impl ObjFile {
+    /// Names of the materials that faces select with usemtl, in the order they first appear.
+    pub fn get_material_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for name in self.groups.iter().filter_map(|group| group.material.as_deref()) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ObjError> {
+        let file = File::open(path).map_err(|error| ObjError {
+            path: path.to_path_buf(),
+            line: None,
+            message: error.to_string(),
+        })?;
+        Self::parse(path, io::BufReader::new(file))
+    }
+
+    /// Parses OBJ statements from any reader, `path` is only used for error messages and to
+    /// resolve material libraries.
+    pub fn parse<R: BufRead>(path: &Path, reader: R) -> Result<Self, ObjError> {
+        let mut parser = ObjParser::default();
+        let mut statement = String::new();
+        let mut statement_line = 0;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| ObjError {
+                path: path.to_path_buf(),
+                line: Some(index + 1),
+                message: error.to_string(),
+            })?;
+
+            if statement.is_empty() {
+                statement_line = index + 1;
+            }
+
+            // A trailing backslash continues the statement on the next line
+            if let Some(continued) = line.strip_suffix('\\') {
+                statement.push_str(continued);
+                statement.push(' ');
+                continue;
+            }
+            statement.push_str(&line);
+
+            parser.parse_statement(path, &statement).map_err(|message| ObjError {
+                path: path.to_path_buf(),
+                line: Some(statement_line),
+                message,
+            })?;
+            statement.clear();
+        }
+
+        if !statement.is_empty() {
+            parser.parse_statement(path, &statement).map_err(|message| ObjError {
+                path: path.to_path_buf(),
+                line: Some(statement_line),
+                message,
+            })?;
+        }
+
+        Ok(Self {
+            vertices: parser.vertices,
+            indices: parser.indices,
+            groups: parser.groups,
+            material_libraries: parser.material_libraries,
+        })
+    }
+}
+
+#[derive(Default)]
+struct ObjParser {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    texture_coordinates: Vec<Vec2>,
+    vertices: Vec<ObjVertex>,
+    // Faces may reference the same position/texture coordinate/normal triple many times
+    vertex_lookup: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+    indices: Vec<u32>,
+    groups: Vec<ObjGroup>,
+    material_libraries: Vec<PathBuf>,
+    current_group: String,
+    current_material: Option<String>,
+}
+
+impl ObjParser {
+    fn parse_statement(&mut self, path: &Path, statement: &str) -> Result<(), String> {
+        let statement = statement.split_once('#').map_or(statement, |(content, _)| content);
+        let mut tokens = statement.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            return Ok(());
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                // Some exporters append a w component or vertex colors, only xyz is used
+                let values = parse_floats(keyword, &arguments, 3, 7)?;
+                self.positions.push(Vec3::new(values[0], values[1], values[2]));
+            }
+            "vn" => {
+                let values = parse_floats(keyword, &arguments, 3, 3)?;
+                self.normals.push(Vec3::new(values[0], values[1], values[2]).normalize_or_zero());
+            }
+            "vt" => {
+                let values = parse_floats(keyword, &arguments, 1, 3)?;
+                self.texture_coordinates
+                    .push(Vec2::new(values[0], values.get(1).copied().unwrap_or(0.0)));
+            }
+            "f" => self.parse_face(&arguments)?,
+            "g" | "o" => self.current_group = arguments.join(" "),
+            "usemtl" => {
+                let Some(material) = arguments.first() else {
+                    return Err("usemtl is missing a material name".to_string());
+                };
+                self.current_material = Some((*material).to_string());
+            }
+            "mtllib" => {
+                if arguments.is_empty() {
+                    return Err("mtllib is missing a file name".to_string());
+                }
+                let directory = path.parent().unwrap_or_else(|| Path::new(""));
+                self.material_libraries
+                    .extend(arguments.iter().map(|library| directory.join(library)));
+            }
+            // Smoothing groups, points, lines, free-form geometry and display attributes do not
+            // affect triangle meshes
+            "s" | "p" | "l" | "mg" | "vp" | "cstype" | "deg" | "bmat" | "step" | "curv"
+            | "curv2" | "surf" | "parm" | "trim" | "hole" | "scrv" | "sp" | "end" | "con"
+            | "bevel" | "c_interp" | "d_interp" | "lod" | "usemap" | "maplib" | "shadow_obj"
+            | "trace_obj" | "ctech" | "stech" => {}
+            _ => return Err(format!("unknown statement '{keyword}'")),
+        }
+
+        Ok(())
+    }
+
+    fn parse_face(&mut self, arguments: &[&str]) -> Result<(), String> {
+        if arguments.len() < 3 {
+            return Err(format!("face needs at least 3 vertices, found {}", arguments.len()));
+        }
+
+        let polygon = arguments
+            .iter()
+            .map(|argument| self.parse_face_vertex(argument))
+            .collect::<Result<Vec<u32>, String>>()?;
+
+        let triangles = if polygon.len() == 3 {
+            vec![[0, 1, 2]]
+        } else {
+            let points: Vec<Vec3> =
+                polygon.iter().map(|&index| self.vertices[index as usize].position).collect();
+            triangulate_polygon(&points)
+        };
+
+        for triangle in &triangles {
+            self.indices.extend(triangle.map(|corner| polygon[corner]));
+        }
+        self.add_to_current_group(triangles.len());
+
+        Ok(())
+    }
+
+    /// Resolves a `v`, `v/vt`, `v//vn` or `v/vt/vn` reference into an index of the vertex list.
+    fn parse_face_vertex(&mut self, argument: &str) -> Result<u32, String> {
+        let mut references = argument.split('/');
+        let position = references.next().unwrap_or_default();
+        let texture_coordinate = references.next().filter(|reference| !reference.is_empty());
+        let normal = references.next().filter(|reference| !reference.is_empty());
+        if references.next().is_some() {
+            return Err(format!("invalid face vertex '{argument}'"));
+        }
+
+        let position = resolve_index(position, self.positions.len(), "vertex")?;
+        let texture_coordinate = texture_coordinate
+            .map(|reference| {
+                resolve_index(reference, self.texture_coordinates.len(), "texture coordinate")
+            })
+            .transpose()?;
+        let normal = normal
+            .map(|reference| resolve_index(reference, self.normals.len(), "normal"))
+            .transpose()?;
+
+        let key = (position, texture_coordinate, normal);
+        if let Some(&index) = self.vertex_lookup.get(&key) {
+            return Ok(index);
+        }
+
+        let index = u32::try_from(self.vertices.len())
+            .map_err(|_| "too many vertices for a single mesh".to_string())?;
+        self.vertices.push(ObjVertex {
+            position: self.positions[position],
+            normal: normal.map(|normal| self.normals[normal]),
+            texture_coordinate: texture_coordinate
+                .map(|texture_coordinate| self.texture_coordinates[texture_coordinate]),
+        });
+        self.vertex_lookup.insert(key, index);
+
+        Ok(index)
+    }
+
+    fn add_to_current_group(&mut self, triangle_count: usize) {
+        let first_triangle = self.indices.len() / 3 - triangle_count;
+
+        if let Some(group) = self.groups.last_mut() {
+            if group.name == self.current_group
+                && group.material == self.current_material
+                && group.first_triangle + group.triangle_count == first_triangle
+            {
+                group.triangle_count += triangle_count;
+                return;
+            }
+        }
+
+        self.groups.push(ObjGroup {
+            name: self.current_group.clone(),
+            material: self.current_material.clone(),
+            first_triangle,
+            triangle_count,
+        });
+    }
+}
+
+fn parse_floats(
+    keyword: &str,
+    arguments: &[&str],
+    min_count: usize,
+    max_count: usize,
+) -> Result<Vec<f32>, String> {
+    if arguments.len() < min_count || arguments.len() > max_count {
+        let expected = if min_count == max_count {
+            min_count.to_string()
+        } else {
+            format!("{min_count} to {max_count}")
+        };
+        return Err(format!("{keyword} expects {expected} values, found {}", arguments.len()));
+    }
+
+    arguments
+        .iter()
+        .map(|argument| {
+            argument.parse::<f32>().map_err(|_| format!("invalid number '{argument}' in {keyword}"))
+        })
+        .collect()
+}
+
+/// OBJ indices start at 1, negative indices count backwards from the last element defined so far.
+fn resolve_index(reference: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let index =
+        reference.parse::<i64>().map_err(|_| format!("invalid {kind} index '{reference}'"))?;
+    let count = i64::try_from(count).unwrap_or(i64::MAX);
+
+    let resolved = match index {
+        0 => return Err(format!("{kind} index 0 is not allowed, indices start at 1")),
+        1.. => index - 1,
+        _ => count + index,
+    };
+
+    if resolved < 0 || resolved >= count {
+        return Err(format!("{kind} index {index} is out of range, {count} defined so far"));
+    }
+
+    Ok(usize::try_from(resolved).unwrap_or_default())
+}
+
+/// Ear clipping triangulation of a planar polygon, keeping the winding order of the polygon.
+/// Falls back to a triangle fan for degenerate polygons.
+fn triangulate_polygon(points: &[Vec3]) -> Vec<[usize; 3]> {
+    let fan = || (1..points.len() - 1).map(|i| [0, i, i + 1]).collect();
+
+    // Newell's method gives a robust normal even for concave polygons
+    let mut normal = Vec3::ZERO;
+    for (i, current) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        normal += (*current - next).cross(*current + next);
+    }
+    if normal.length_squared() <= f32::EPSILON * f32::EPSILON {
+        return fan();
+    }
+
+    // Project onto the plane most aligned with the polygon, oriented so the polygon is counterclockwise
+    let abs_normal = normal.abs();
+    let (axis_u, axis_v, sign) = if abs_normal.x >= abs_normal.y && abs_normal.x >= abs_normal.z {
+        (1, 2, normal.x.signum())
+    } else if abs_normal.y >= abs_normal.z {
+        (2, 0, normal.y.signum())
+    } else {
+        (0, 1, normal.z.signum())
+    };
+    let projected: Vec<Vec2> =
+        points.iter().map(|point| Vec2::new(point[axis_u], point[axis_v] * sign)).collect();
+
+    let cross = |a: Vec2, b: Vec2, c: Vec2| (b - a).perp_dot(c - a);
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&i| {
+            let [a, b, c] =
+                [remaining[(i + count - 1) % count], remaining[i], remaining[(i + 1) % count]];
+            let [pa, pb, pc] = [projected[a], projected[b], projected[c]];
+            if cross(pa, pb, pc) <= 0.0 {
+                return false; // reflex or degenerate corner
+            }
+            remaining.iter().all(|&other| {
+                other == a
+                    || other == b
+                    || other == c
+                    || cross(pa, pb, projected[other]) < 0.0
+                    || cross(pb, pc, projected[other]) < 0.0
+                    || cross(pc, pa, projected[other]) < 0.0
+            })
+        });
+
+        let Some(i) = ear else {
+            // Self intersecting or numerically degenerate, fan the rest
+            triangles
+                .extend((1..count - 1).map(|j| [remaining[0], remaining[j], remaining[j + 1]]));
+            return triangles;
+        };
+
+        triangles.push([
+            remaining[(i + count - 1) % count],
+            remaining[i],
+            remaining[(i + 1) % count],
+        ]);
+        remaining.remove(i);
+    }
+
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ObjFile, ObjError> {
+        ObjFile::parse(Path::new("test.obj"), source.as_bytes())
+    }
+
+    fn triangle_positions(obj_file: &ObjFile) -> Vec<[Vec3; 3]> {
+        obj_file
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| [0, 1, 2].map(|i| obj_file.vertices[triangle[i] as usize].position))
+            .collect()
+    }
+
+    #[test]
+    fn triangulates_quads() {
+        let obj_file = parse("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n").unwrap();
+
+        assert_eq!(obj_file.vertices.len(), 4);
+        assert_eq!(obj_file.indices.len(), 6);
+        for [a, b, c] in triangle_positions(&obj_file) {
+            assert!((b - a).cross(c - a).z > 0.0, "triangle lost the winding order");
+        }
+    }
+
+    #[test]
+    fn triangulates_concave_polygons() {
+        // An L shape, a fan from the first corner would leave the polygon
+        let obj_file =
+            parse("v 0 0 0\nv 2 0 0\nv 2 1 0\nv 1 1 0\nv 1 2 0\nv 0 2 0\nf 1 2 3 4 5 6\n").unwrap();
+
+        let triangles = triangle_positions(&obj_file);
+        assert_eq!(triangles.len(), 4);
+        let area: f32 = triangles.iter().map(|[a, b, c]| (*b - *a).cross(*c - *a).z * 0.5).sum();
+        assert!((area - 3.0).abs() < 1e-5);
+        assert!(triangles.iter().all(|[a, b, c]| (*b - *a).cross(*c - *a).z > 0.0));
+    }
+
+    #[test]
+    fn reads_normal_and_texture_coordinate_references() {
+        let obj_file = parse(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 2\n\
+             f 1//1 2//1 3//1\nf 1/1/1 2/2/1 3/3/1\n",
+        )
+        .unwrap();
+
+        assert_eq!(obj_file.indices.len(), 6);
+        let first = obj_file.vertices[obj_file.indices[0] as usize];
+        assert_eq!(first.normal, Some(Vec3::Z));
+        assert_eq!(first.texture_coordinate, None);
+
+        let second = obj_file.vertices[obj_file.indices[4] as usize];
+        assert_eq!(second.normal, Some(Vec3::Z));
+        assert_eq!(second.texture_coordinate, Some(Vec2::new(1.0, 0.0)));
+
+        // The same position with different attributes becomes a separate vertex
+        assert_eq!(obj_file.vertices.len(), 6);
+    }
+
+    #[test]
+    fn resolves_negative_indices() {
+        let obj_file =
+            parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf -3//-1 -2//-1 -1//-1\n").unwrap();
+
+        assert_eq!(triangle_positions(&obj_file), vec![[Vec3::ZERO, Vec3::X, Vec3::Y]]);
+        assert!(obj_file.vertices.iter().all(|vertex| vertex.normal == Some(Vec3::Z)));
+    }
+
+    #[test]
+    fn rejects_out_of_range_indices() {
+        let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").err().unwrap();
+        assert_eq!(error.line, Some(4));
+        assert!(error.message.contains("out of range"), "{error}");
+
+        let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -4 1 2\n").err().unwrap();
+        assert!(error.message.contains("out of range"), "{error}");
+
+        let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1//1 2//1 3//1\n").err().unwrap();
+        assert!(error.message.contains("normal index"), "{error}");
+
+        let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n").err().unwrap();
+        assert!(error.message.contains("index 0"), "{error}");
+    }
+
+    #[test]
+    fn rejects_malformed_vertices() {
+        let error = parse("v 0 0 0\nv 1 zero 0\n").err().unwrap();
+        assert_eq!(error.line, Some(2));
+        assert!(error.message.contains("invalid number 'zero'"), "{error}");
+
+        let error = parse("v 0 0\n").err().unwrap();
+        assert_eq!(error.line, Some(1));
+        assert!(error.message.contains("expects 3 to 7 values"), "{error}");
+    }
+
+    #[test]
+    fn keeps_materials_per_group() {
+        let obj_file = parse(
+            "mtllib scene.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nusemtl glass\nf 1 2 3\n\
+             f 3 2 1\nusemtl metal\nf 1 2 3\nusemtl glass\nf 1 2 3\n",
+        )
+        .unwrap();
+
+        assert_eq!(obj_file.material_libraries, vec![PathBuf::from("scene.mtl")]);
+        let groups: Vec<_> = obj_file
+            .groups
+            .iter()
+            .map(|group| (group.material.as_deref(), group.first_triangle, group.triangle_count))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![(None, 0, 1), (Some("glass"), 1, 2), (Some("metal"), 3, 1), (Some("glass"), 4, 1)]
+        );
+        assert_eq!(obj_file.get_material_names(), vec!["glass", "metal"]);
+    }
+
+    #[test]
+    fn rejects_unknown_statements() {
+        let error = parse("v 0 0 0\ns off\nvertex 1 0 0\n").err().unwrap();
+        assert_eq!(error.line, Some(3));
+        assert!(error.message.contains("unknown statement 'vertex'"), "{error}");
+    }
+}
@@ -1,5 +1,6 @@
 use crate::hitrecord::HitRecord;
 use crate::materials::MaterialEnum;
+use crate::math::aabb::Aabb;
 use crate::ray::Ray;
 use enum_dispatch::enum_dispatch;
 use glam::Vec3;
@@ -19,5 +20,8 @@ impl<'mm> ObjectProperties<'mm> {
 #[enum_dispatch]
 pub trait Object<'mm>: Sync + Send {
     fn hit(&self, ray: &Ray, hit_record: &mut HitRecord<'mm>, is_shadow_ray: bool) -> bool;
-    fn update(&self, delta_time: f32);
+    /// Called once per frame before rendering.
+    fn update(&mut self, delta_time: f32);
+    /// World space bounds of the object, `None` for unbounded objects such as planes.
+    fn get_bounding_box(&self) -> Option<Aabb>;
 }
@@ -1,7 +1,8 @@
 use super::object_properties::{Object, ObjectProperties};
 use crate::hitrecord::HitRecord;
+use crate::math::{aabb::Aabb, sampling::orthonormal_basis};
 use crate::ray::Ray;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 
 pub struct Plane<'mm> {
     pub object_properties: ObjectProperties<'mm>,
@@ -41,8 +42,17 @@ impl<'mm> Object<'mm> for Plane<'mm> {
         hit_record.hitpoint = ray.origin + (intersection_distance * ray.direction);
         hit_record.normal = self.normal;
         hit_record.material = Some(self.object_properties.material);
+        // World units along two tangents of the plane, starting at its position
+        let (tangent, bitangent) = orthonormal_basis(self.normal);
+        let offset = hit_record.hitpoint - self.object_properties.position;
+        hit_record.uv = Vec2::new(offset.dot(tangent), offset.dot(bitangent));
+        hit_record.set_tangent_frame(tangent, bitangent);
         true
     }
 
-    fn update(&self, _delta_time: f32) {}
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn get_bounding_box(&self) -> Option<Aabb> {
+        None
+    }
 }
@@ -1,6 +1,9 @@
 use super::object_properties::{Object, ObjectProperties};
 use crate::hitrecord::HitRecord;
+use crate::math::aabb::Aabb;
 use crate::ray::Ray;
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
 
 pub struct Sphere<'mm> {
     pub object_properties: ObjectProperties<'mm>,
@@ -18,10 +21,9 @@ impl<'mm> Object<'mm> for Sphere<'mm> {
         // Vector from the ray origin to the sphere center
         let ray_to_sphere = self.object_properties.position - ray.origin;
 
+        // Negative when the center is behind the ray origin, rays starting inside the sphere still
+        // leave it at tca + thc, so the range checks below decide which intersection counts
         let tca = ray_to_sphere.dot(ray.direction);
-        if tca < 0.0 {
-            return false; // ray is pointing away from the sphere
-        }
 
         // Square of the distance from the sphere center to the ray's closest approach
         let approach_distance_sq = ray_to_sphere.reject_from(ray.direction).length_squared();
@@ -54,9 +56,24 @@ impl<'mm> Object<'mm> for Sphere<'mm> {
         hit_record.hitpoint = ray.origin + t0 * ray.direction;
         hit_record.normal = (hit_record.hitpoint - self.object_properties.position).normalize();
         hit_record.material = Some(self.object_properties.material);
+        // Longitude and latitude, u runs around the y axis and v from the bottom to the top
+        let normal = hit_record.normal;
+        hit_record.uv = Vec2::new(
+            0.5 + (-normal.z).atan2(normal.x) / (2.0 * PI),
+            0.5 + normal.y.clamp(-1.0, 1.0).asin() / PI,
+        );
+        // Along the circle of latitude and towards the north pole, the poles fall back on any frame
+        hit_record.set_tangent_frame(Vec3::new(normal.z, 0.0, -normal.x), Vec3::Y);
 
         true
     }
 
-    fn update(&self, _delta_time: f32) {}
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn get_bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.object_properties.position - self.radius,
+            self.object_properties.position + self.radius,
+        ))
+    }
 }
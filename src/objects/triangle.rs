@@ -1,7 +1,8 @@
 use super::object_properties::{Object, ObjectProperties};
 use crate::hitrecord::HitRecord;
+use crate::math::aabb::Aabb;
 use crate::ray::Ray;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 
 #[derive(Copy, Clone)]
 pub enum CullMode {
@@ -14,7 +15,11 @@ pub struct Triangle<'mm> {
     pub object_properties: ObjectProperties<'mm>,
     pub vertices: [Vec3; 3],
     pub normal: Vec3,
+    pub vertex_normals: Option<[Vec3; 3]>,
+    pub texture_coordinates: Option<[Vec2; 3]>,
     pub cull_mode: CullMode,
+    /// Derivatives of the position along u and v, constant over the triangle
+    position_derivatives: (Vec3, Vec3),
 }
 
 impl<'mm> Triangle<'mm> {
@@ -22,9 +27,70 @@ impl<'mm> Triangle<'mm> {
         object_properties: ObjectProperties<'mm>,
         vertices: [Vec3; 3],
         cull_mode: CullMode,
+    ) -> Self {
+        Self::new_with_attributes(object_properties, vertices, None, None, cull_mode)
+    }
+
+    /// Creates a triangle that also carries the per-vertex normals and texture coordinates of a mesh
+    pub fn new_with_attributes(
+        object_properties: ObjectProperties<'mm>,
+        vertices: [Vec3; 3],
+        vertex_normals: Option<[Vec3; 3]>,
+        texture_coordinates: Option<[Vec2; 3]>,
+        cull_mode: CullMode,
     ) -> Self {
         let normal = (vertices[2] - vertices[0]).cross(vertices[1] - vertices[0]).normalize();
-        Self { object_properties, vertices, normal, cull_mode }
+        let position_derivatives = Self::get_position_derivatives(vertices, texture_coordinates);
+        Self {
+            object_properties,
+            vertices,
+            normal,
+            vertex_normals,
+            texture_coordinates,
+            cull_mode,
+            position_derivatives,
+        }
+    }
+
+    /// Solves the edges for how the position changes with u and v. Without texture coordinates, or
+    /// when they don't span an area, u and v are the barycentric weights of the second and third
+    /// vertex and the derivatives are simply the edges.
+    fn get_position_derivatives(
+        vertices: [Vec3; 3],
+        texture_coordinates: Option<[Vec2; 3]>,
+    ) -> (Vec3, Vec3) {
+        let edges = (vertices[1] - vertices[0], vertices[2] - vertices[0]);
+        let Some(texture_coordinates) = texture_coordinates else {
+            return edges;
+        };
+
+        let uv_edges = (
+            texture_coordinates[1] - texture_coordinates[0],
+            texture_coordinates[2] - texture_coordinates[0],
+        );
+        let determinant = uv_edges.0.perp_dot(uv_edges.1);
+        if determinant.abs() < f32::EPSILON {
+            return edges;
+        }
+
+        (
+            (edges.0 * uv_edges.1.y - edges.1 * uv_edges.0.y) / determinant,
+            (edges.1 * uv_edges.0.x - edges.0 * uv_edges.1.x) / determinant,
+        )
+    }
+
+    fn interpolate_normal(&self, vertex_normals: [Vec3; 3], edge_areas: [f32; 3]) -> Vec3 {
+        let normal = (vertex_normals[2] * edge_areas[0]
+            + vertex_normals[0] * edge_areas[1]
+            + vertex_normals[1] * edge_areas[2])
+            .normalize_or_zero();
+
+        // Keep the shading normal on the same side as the surface so offsets stay outside the mesh
+        if normal.dot(self.normal) <= 0.0 {
+            self.normal
+        } else {
+            normal
+        }
     }
 }
 
@@ -55,29 +121,56 @@ impl<'mm> Object<'mm> for Triangle<'mm> {
         let t = (self.vertices[0] + self.object_properties.position - ray.origin).dot(self.normal)
             / v_dot_n;
 
-        if t < ray.t_min || t > ray.t_max || (t > hit_record.t && !is_shadow_ray) {
+        if t < ray.t_min || t > ray.t_max {
             return false;
         }
 
         let hitpoint = ray.origin + t * ray.direction;
 
-        // Barycentric containment test for the intersection point
-        for i in 0..3 {
+        // Barycentric containment test for the intersection point, the area spanned by an edge and the
+        // hitpoint is the weight of the vertex opposite to that edge
+        let mut edge_areas = [0.0; 3];
+        for (i, edge_area) in edge_areas.iter_mut().enumerate() {
             let edge = self.vertices[(i + 1) % 3] + self.object_properties.position
                 - (self.vertices[i] + self.object_properties.position);
             let side_to_point = self.vertices[i] + self.object_properties.position - hitpoint;
-            if self.normal.dot(edge.cross(side_to_point)) < 0.0 {
+            *edge_area = self.normal.dot(edge.cross(side_to_point));
+            if *edge_area < 0.0 {
                 return false;
             }
         }
 
+        if is_shadow_ray {
+            return true;
+        }
+
         hit_record.t = t;
         hit_record.hitpoint = hitpoint;
-        hit_record.normal = self.normal;
+        hit_record.normal = self.vertex_normals.map_or(self.normal, |vertex_normals| {
+            self.interpolate_normal(vertex_normals, edge_areas)
+        });
         hit_record.material = Some(self.object_properties.material);
+        // Without texture coordinates the barycentric coordinates of the hitpoint are used
+        let area = edge_areas.iter().sum::<f32>().max(f32::EPSILON);
+        let weights = [edge_areas[1], edge_areas[2], edge_areas[0]].map(|weight| weight / area);
+        hit_record.uv = self.texture_coordinates.map_or_else(
+            || Vec2::new(weights[1], weights[2]),
+            |texture_coordinates| {
+                texture_coordinates[0] * weights[0]
+                    + texture_coordinates[1] * weights[1]
+                    + texture_coordinates[2] * weights[2]
+            },
+        );
+        hit_record.set_tangent_frame(self.position_derivatives.0, self.position_derivatives.1);
 
         true
     }
 
-    fn update(&self, _delta_time: f32) {}
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn get_bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::from_points(
+            &self.vertices.map(|vertex| vertex + self.object_properties.position),
+        ))
+    }
 }
@@ -1,18 +1,18 @@
 use super::{
+    bvh::Bvh,
+    obj_file::{ObjError, ObjFile},
     object_properties::{Object, ObjectProperties},
     triangle::{CullMode, Triangle},
 };
-use crate::{hitrecord::HitRecord, ray::Ray};
+use crate::{hitrecord::HitRecord, materials::MaterialEnum, math::aabb::Aabb, ray::Ray};
 use glam::Vec3;
+use std::{collections::HashMap, path::Path};
 
-use std::{
-    fs::File,
-    io::{self, BufRead},
-};
-
+#[allow(clippy::struct_field_names)]
 pub struct TriangleMesh<'mm> {
     pub object_properties: ObjectProperties<'mm>,
     pub triangle_mesh: Vec<Triangle<'mm>>,
+    bvh: Bvh,
 }
 
 impl<'mm> TriangleMesh<'mm> {
@@ -40,71 +40,148 @@ impl<'mm> TriangleMesh<'mm> {
             triangle_mesh.push(triangle);
         }
 
-        Self { object_properties, triangle_mesh }
+        Self::from_triangles(object_properties, triangle_mesh)
     }
 
+    /// Loads an OBJ file, keeping the normals and texture coordinates of the file on every
+    /// triangle. Faces after a usemtl statement get the material `materials` maps its name to,
+    /// all other faces the material of `object_properties`. Material libraries are not read.
     pub fn new_from_obj(
         object_properties: ObjectProperties<'mm>,
-        obj_file_name: &str,
+        path: &Path,
         cull_mode: CullMode,
-    ) -> Self {
-        let path_to_obj = format!("assets/obj_models/{obj_file_name}.obj");
-
-        let regex_vertices =
-            regex::Regex::new(r"(^v\s+(-?\d+.?\d+)\s(-?\d+.?\d+)\s(-?\d+.?\d+)\s*)").unwrap(); //x
-        let regex_faces = regex::Regex::new(r"(^f\s(?:([0-9]+)\s([0-9]+)\s([0-9]+))\s*)").unwrap();
-        //x
-
-        let mut vertex_buffer: Vec<Vec3> = Vec::new();
-        let mut index_buffer: Vec<u32> = Vec::new();
-
-        let file = File::open(path_to_obj).unwrap();
-        let lines = io::BufReader::new(file).lines();
-
-        for line in lines.flatten() {
-            regex_vertices.captures(&line).map_or_else(
-                || {
-                    if let Some(captures) = regex_faces.captures(&line) {
-                        for i in (2..=4).rev() {
-                            let index = captures[i].parse::<u32>().unwrap() - 1;
-                            index_buffer.push(index);
-                        }
-                    }
-                },
-                |captures| {
-                    if captures.len() > 3 {
-                        let x = captures[2].parse::<f32>().unwrap();
-                        let y = captures[3].parse::<f32>().unwrap();
-                        let z = captures[4].parse::<f32>().unwrap();
-                        vertex_buffer.push(Vec3::new(x, y, z));
-                    }
-                },
-            );
+        materials: &HashMap<&str, &'mm MaterialEnum>,
+    ) -> Result<Self, ObjError> {
+        let obj_file = ObjFile::load(path)?;
+
+        let material_names = obj_file.get_material_names();
+        if let Some(name) = materials.keys().find(|name| !material_names.contains(name)) {
+            return Err(ObjError {
+                path: path.to_path_buf(),
+                line: None,
+                message: format!("no faces use the material '{name}'"),
+            });
         }
-        let mut triangle_mesh: Vec<Triangle<'mm>> = Vec::with_capacity(index_buffer.len() / 3);
 
-        for i in 0..index_buffer.len() / 3 {
-            triangle_mesh.push(Triangle::new(
-                ObjectProperties {
-                    position: object_properties.position,
-                    material: object_properties.material,
-                },
-                [
-                    vertex_buffer[index_buffer[i * 3] as usize],
-                    vertex_buffer[index_buffer[i * 3 + 1] as usize],
-                    vertex_buffer[index_buffer[i * 3 + 2] as usize],
-                ],
-                cull_mode,
-            ));
+        // Groups are consecutive runs of triangles, so they line up with the triangles in order
+        let triangle_materials = obj_file.groups.iter().flat_map(|group| {
+            let material = group
+                .material
+                .as_deref()
+                .and_then(|name| materials.get(name).copied())
+                .unwrap_or(object_properties.material);
+            (0..group.triangle_count).map(move |_| material)
+        });
+
+        let triangle_mesh = obj_file
+            .indices
+            .chunks_exact(3)
+            .zip(triangle_materials)
+            .map(|(indices, material)| {
+                // Triangle expects the opposite winding order of the OBJ format
+                let corners = [indices[2], indices[1], indices[0]]
+                    .map(|index| obj_file.vertices[index as usize]);
+
+                Triangle::new_with_attributes(
+                    ObjectProperties::new(object_properties.position, material),
+                    corners.map(|corner| corner.position),
+                    corners
+                        .iter()
+                        .all(|corner| corner.normal.is_some())
+                        .then(|| corners.map(|corner| corner.normal.unwrap_or_default())),
+                    corners.iter().all(|corner| corner.texture_coordinate.is_some()).then(|| {
+                        corners.map(|corner| corner.texture_coordinate.unwrap_or_default())
+                    }),
+                    cull_mode,
+                )
+            })
+            .collect();
+
+        Ok(Self::from_triangles(object_properties, triangle_mesh))
+    }
+
+    /// Gives every triangle without vertex normals smooth normals, averaged over the faces sharing
+    /// each vertex and weighted by their angle at that vertex. Faces whose normals differ by more
+    /// than `crease_angle` degrees do not contribute to each other so hard edges stay sharp.
+    pub fn generate_vertex_normals(&mut self, crease_angle: f32) {
+        let cos_crease_angle = crease_angle.to_radians().cos();
+
+        // Triangles only store positions, so faces are connected through identical vertex positions
+        let mut faces_at_vertex: HashMap<[u32; 3], Vec<(usize, usize)>> = HashMap::new();
+        for (triangle_index, triangle) in self.triangle_mesh.iter().enumerate() {
+            for (corner, vertex) in triangle.vertices.iter().enumerate() {
+                faces_at_vertex
+                    .entry(vertex.to_array().map(f32::to_bits))
+                    .or_default()
+                    .push((triangle_index, corner));
+            }
+        }
+
+        let corner_angle = |triangle: &Triangle<'_>, corner: usize| {
+            let vertex = triangle.vertices[corner];
+            (triangle.vertices[(corner + 1) % 3] - vertex)
+                .angle_between(triangle.vertices[(corner + 2) % 3] - vertex)
+        };
+
+        let generated_normals: Vec<Option<[Vec3; 3]>> = self
+            .triangle_mesh
+            .iter()
+            .map(|triangle| {
+                if triangle.vertex_normals.is_some() {
+                    return None;
+                }
+
+                Some([0, 1, 2].map(|corner| {
+                    let key = triangle.vertices[corner].to_array().map(f32::to_bits);
+                    let normal: Vec3 = faces_at_vertex[&key]
+                        .iter()
+                        .map(|&(neighbor_index, neighbor_corner)| {
+                            (&self.triangle_mesh[neighbor_index], neighbor_corner)
+                        })
+                        .filter(|(neighbor, _)| {
+                            neighbor.normal.dot(triangle.normal) >= cos_crease_angle
+                        })
+                        .map(|(neighbor, neighbor_corner)| {
+                            neighbor.normal * corner_angle(neighbor, neighbor_corner)
+                        })
+                        .sum();
+                    normal.try_normalize().unwrap_or(triangle.normal)
+                }))
+            })
+            .collect();
+
+        for (triangle, vertex_normals) in self.triangle_mesh.iter_mut().zip(generated_normals) {
+            if vertex_normals.is_some() {
+                triangle.vertex_normals = vertex_normals;
+            }
         }
-        Self { object_properties, triangle_mesh }
+    }
+
+    fn from_triangles(
+        object_properties: ObjectProperties<'mm>,
+        triangle_mesh: Vec<Triangle<'mm>>,
+    ) -> Self {
+        let triangle_bounds: Vec<Aabb> = triangle_mesh
+            .iter()
+            .map(|triangle| triangle.get_bounding_box().unwrap_or_default())
+            .collect();
+        let bvh = Bvh::new(&triangle_bounds);
+
+        Self { object_properties, triangle_mesh, bvh }
     }
 }
 
 impl<'mm> Object<'mm> for TriangleMesh<'mm> {
     fn hit(&self, ray: &Ray, hit_record: &mut HitRecord<'mm>, is_shadow_ray: bool) -> bool {
-        self.triangle_mesh.iter().any(|triangle| triangle.hit(ray, hit_record, is_shadow_ray))
+        // The BVH shrinks the ray after every hit, so the last triangle that reports a hit is the closest one
+        self.bvh.hit(ray, is_shadow_ray, |index, ray| {
+            self.triangle_mesh[index].hit(ray, hit_record, is_shadow_ray).then_some(hit_record.t)
+        })
     }
 
-    fn update(&self, _delta_time: f32) {}
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn get_bounding_box(&self) -> Option<Aabb> {
+        Some(self.bvh.bounds())
+    }
 }
@@ -0,0 +1,188 @@
+use glam::{Vec2, Vec3};
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    fmt,
+    str::FromStr,
+};
+
+/// How the camera maps the image onto rays, in camera space where the camera looks along -Z with
+/// +Y up.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Projection {
+    Perspective,
+    /// Parallel rays through a window of `orthographic_height` world units, for elevations
+    Orthographic,
+    /// Full 360° x 180° panorama, the center of the image is the view direction
+    Equirectangular,
+    /// Equidistant fisheye in a circle that fits the image, pixels outside of it stay black
+    Fisheye,
+    /// One square 90° face of a cube map around the camera
+    CubeFace(CubeFace),
+}
+
+/// The side faces turn the camera around its up axis, the top and bottom faces tilt it up or down.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    const ALL: [Self; 6] = [
+        Self::PositiveX,
+        Self::NegativeX,
+        Self::PositiveY,
+        Self::NegativeY,
+        Self::PositiveZ,
+        Self::NegativeZ,
+    ];
+
+    /// View direction and up vector of the face in camera space.
+    const fn get_frame(self) -> (Vec3, Vec3) {
+        match self {
+            Self::PositiveX => (Vec3::X, Vec3::Y),
+            Self::NegativeX => (Vec3::NEG_X, Vec3::Y),
+            Self::PositiveY => (Vec3::Y, Vec3::Z),
+            Self::NegativeY => (Vec3::NEG_Y, Vec3::NEG_Z),
+            Self::PositiveZ => (Vec3::Z, Vec3::Y),
+            Self::NegativeZ => (Vec3::NEG_Z, Vec3::Y),
+        }
+    }
+
+    const fn get_name(self) -> &'static str {
+        match self {
+            Self::PositiveX => "positive_x",
+            Self::NegativeX => "negative_x",
+            Self::PositiveY => "positive_y",
+            Self::NegativeY => "negative_y",
+            Self::PositiveZ => "positive_z",
+            Self::NegativeZ => "negative_z",
+        }
+    }
+}
+
+impl Projection {
+    /// Camera space origin and (not normalized) direction of the ray through `image_position`,
+    /// which runs from -1 to 1 over the image with y pointing up. `None` when the position lies
+    /// outside of what the projection covers.
+    pub fn get_local_ray(
+        self,
+        image_position: Vec2,
+        aspect_ratio: f32,
+        scale_factor: f32,
+        orthographic_height: f32,
+        fisheye_fov_radians: f32,
+    ) -> Option<(Vec3, Vec3)> {
+        match self {
+            Self::Perspective => Some((
+                Vec3::ZERO,
+                Vec3::new(
+                    image_position.x * aspect_ratio * scale_factor,
+                    image_position.y * scale_factor,
+                    -1.0,
+                ),
+            )),
+            Self::Orthographic => {
+                let half_height = orthographic_height / 2.0;
+                Some((
+                    Vec3::new(
+                        image_position.x * aspect_ratio * half_height,
+                        image_position.y * half_height,
+                        0.0,
+                    ),
+                    Vec3::NEG_Z,
+                ))
+            }
+            Self::Equirectangular => {
+                let longitude = image_position.x * PI;
+                let latitude = image_position.y * FRAC_PI_2;
+                let (sin_longitude, cos_longitude) = longitude.sin_cos();
+                let (sin_latitude, cos_latitude) = latitude.sin_cos();
+                Some((
+                    Vec3::ZERO,
+                    Vec3::new(
+                        sin_longitude * cos_latitude,
+                        sin_latitude,
+                        -cos_longitude * cos_latitude,
+                    ),
+                ))
+            }
+            Self::Fisheye => {
+                // Scaled so the circle touches the shorter side of the image
+                let position = Vec2::new(image_position.x * aspect_ratio, image_position.y)
+                    / aspect_ratio.min(1.0);
+                let radius = position.length();
+                if radius > 1.0 {
+                    return None;
+                }
+                let (sin_theta, cos_theta) = (radius * fisheye_fov_radians / 2.0).sin_cos();
+                Some((Vec3::ZERO, (position.normalize_or_zero() * sin_theta).extend(-cos_theta)))
+            }
+            Self::CubeFace(face) => {
+                let (forward, up) = face.get_frame();
+                let right = forward.cross(up);
+                Some((Vec3::ZERO, forward + right * image_position.x + up * image_position.y))
+            }
+        }
+    }
+
+    /// Whether the image lies on a plane, only then the thin lens can focus on a plane.
+    pub const fn is_planar(self) -> bool {
+        matches!(self, Self::Perspective | Self::Orthographic)
+    }
+
+    /// The next projection, for cycling through them at runtime.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Perspective => Self::Orthographic,
+            Self::Orthographic => Self::Equirectangular,
+            Self::Equirectangular => Self::Fisheye,
+            Self::Fisheye => Self::CubeFace(CubeFace::PositiveX),
+            Self::CubeFace(CubeFace::PositiveX) => Self::CubeFace(CubeFace::NegativeX),
+            Self::CubeFace(CubeFace::NegativeX) => Self::CubeFace(CubeFace::PositiveY),
+            Self::CubeFace(CubeFace::PositiveY) => Self::CubeFace(CubeFace::NegativeY),
+            Self::CubeFace(CubeFace::NegativeY) => Self::CubeFace(CubeFace::PositiveZ),
+            Self::CubeFace(CubeFace::PositiveZ) => Self::CubeFace(CubeFace::NegativeZ),
+            Self::CubeFace(CubeFace::NegativeZ) => Self::Perspective,
+        }
+    }
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Perspective => f.write_str("perspective"),
+            Self::Orthographic => f.write_str("orthographic"),
+            Self::Equirectangular => f.write_str("equirectangular"),
+            Self::Fisheye => f.write_str("fisheye"),
+            Self::CubeFace(face) => write!(f, "cube_{}", face.get_name()),
+        }
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "perspective" => Ok(Self::Perspective),
+            "orthographic" => Ok(Self::Orthographic),
+            "equirectangular" => Ok(Self::Equirectangular),
+            "fisheye" => Ok(Self::Fisheye),
+            _ => name
+                .strip_prefix("cube_")
+                .and_then(|face| CubeFace::ALL.into_iter().find(|f| f.get_name() == face))
+                .map(Self::CubeFace)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown projection '{name}', use perspective, orthographic, \
+                         equirectangular, fisheye or cube_<positive|negative>_<x|y|z>"
+                    )
+                }),
+        }
+    }
+}
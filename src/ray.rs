@@ -1,5 +1,6 @@
 use glam::Vec3;
 
+#[derive(Clone, Copy)]
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
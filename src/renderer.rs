@@ -1,14 +1,53 @@
+use crate::aov::Aov;
 use crate::hitrecord::HitRecord;
-use crate::lights::light_properties::Light;
+use crate::lights::{
+    light_manager::LightManager,
+    light_properties::{Light, LightSample},
+};
 use crate::materials::material_properties::Material;
-use crate::math::ColorTypeFunctionality;
+use crate::math::{brdf, reflect, refract, ColorTypeFunctionality};
+use crate::math::{
+    pixel_filter::PixelFilter,
+    sampling::{uniform_sample_disk, Sampler},
+    tone_mapping::{linear_to_srgb, ToneMapper},
+};
 use crate::ray::Ray;
 use crate::world::scenegraph::Scenegraph;
 use crate::{camera::Camera, lights::LightEnum};
-use glam::{Mat4, Vec2, Vec3, Vec4};
+use glam::{Vec2, Vec3};
 use rayon::prelude::*;
 pub type RGBColor = Vec3;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    /// Direct light plus mirror bounces, a full image every frame
+    Whitted,
+    /// Monte Carlo path tracing that refines the image over consecutive frames
+    PathTracer,
+}
+
+/// Light arriving along a camera ray, split up for the direct and indirect AOVs.
+#[derive(Clone, Copy, Default)]
+struct Radiance {
+    direct: RGBColor,
+    indirect: RGBColor,
+}
+
+impl Radiance {
+    fn get_total(self) -> RGBColor {
+        self.direct + self.indirect
+    }
+
+    /// Only light reflected towards the camera by the first surface it hits counts as direct.
+    fn add(&mut self, is_first_hit: bool, radiance: RGBColor) {
+        if is_first_hit {
+            self.direct += radiance;
+        } else {
+            self.indirect += radiance;
+        }
+    }
+}
+
 pub struct Renderer {
     aspect_ratio: f32,
     width: u32,
@@ -16,12 +55,35 @@ pub struct Renderer {
     max_bounces: u32,
     pub are_hard_shadows_enabled: bool,
     pub render_mode: u8,
+    pub integrator: Integrator,
+    /// Every pixel averages `pixel_samples_per_axis²` stratified camera rays
+    pub pixel_samples_per_axis: u32,
+    pub pixel_filter: PixelFilter,
+    /// Exposure in stops, every stop doubles the brightness of the image
+    pub exposure: f32,
+    pub tone_mapper: ToneMapper,
+    /// Encodes the output with the sRGB transfer function instead of writing linear values
+    pub is_srgb_enabled: bool,
+    /// What the framebuffer holds, the rendered image or one of the other passes
+    aov: Aov,
+    /// Linear radiance of the last frame, before exposure and tone mapping
+    framebuffer: Vec<RGBColor>,
+    /// Path traced colors weighted by the pixel filter and the sum of those weights
+    accumulation_buffer: Vec<RGBColor>,
+    accumulation_weights: Vec<f32>,
+    accumulated_samples: u32,
+    // Camera, light manager and scenegraph revisions the accumulated samples were rendered with
+    accumulation_revisions: Option<(u64, u64, u64)>,
 }
 
 #[allow(clippy::cast_precision_loss)]
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_sign_loss)]
 impl Renderer {
+    const MAX_PATH_BOUNCES: u32 = 32;
+    const RUSSIAN_ROULETTE_START_BOUNCE: u32 = 3;
+    const MAX_PIXEL_SAMPLES_PER_AXIS: u32 = 4;
+
     pub fn new(width: u32, height: u32) -> Self {
         let aspect_ratio = width as f32 / height as f32;
         let max_bounces = 4;
@@ -32,47 +94,306 @@ impl Renderer {
             max_bounces,
             are_hard_shadows_enabled: true,
             render_mode: 0,
+            integrator: Integrator::Whitted,
+            pixel_samples_per_axis: 1,
+            pixel_filter: PixelFilter::Box,
+            exposure: 0.0,
+            tone_mapper: ToneMapper::Aces,
+            is_srgb_enabled: true,
+            aov: Aov::Beauty,
+            framebuffer: vec![RGBColor::ZERO; (width * height) as usize],
+            accumulation_buffer: vec![RGBColor::ZERO; (width * height) as usize],
+            accumulation_weights: vec![0.0; (width * height) as usize],
+            accumulated_samples: 0,
+            accumulation_revisions: None,
         }
     }
 
+    /// Renders a frame into the radiance framebuffer, `write_display_pixels` turns it into an
+    /// image that can be shown.
     pub fn render(
-        &self,
-        pixel_data: &mut Vec<u32>,
+        &mut self,
         scenegraph: &Scenegraph<'_>,
         camera: &Camera,
-        lights: &Vec<LightEnum>,
+        light_manager: &LightManager<LightEnum>,
     ) {
-        let camera_look_at: &Mat4 = &camera.look_at;
-        let scale_factor = camera.get_scale_factor();
+        // Taken out of self so the rows can be written in parallel while self is borrowed
+        let mut framebuffer = std::mem::take(&mut self.framebuffer);
+        match self.integrator {
+            Integrator::Whitted => {
+                self.render_whitted(
+                    &mut framebuffer,
+                    scenegraph,
+                    camera,
+                    light_manager.get_lights(),
+                );
+            }
+            Integrator::PathTracer => {
+                self.render_path_traced(&mut framebuffer, scenegraph, camera, light_manager);
+            }
+        }
+        self.framebuffer = framebuffer;
+    }
 
-        // We are grabbing a parallel iterator over rows
-        pixel_data.par_chunks_mut(self.width as usize).enumerate().for_each(|(y, row)| {
-            let mut ray_ss_coords: Vec2 =
-                Vec2 { x: 0.0, y: self.get_ray_world_coord_y(y as u32, scale_factor) };
+    /// Linear radiance of every pixel of the last frame, row by row from the top.
+    pub fn get_framebuffer(&self) -> &[RGBColor] {
+        &self.framebuffer
+    }
 
-            for (x, pixel_data) in row.iter_mut().enumerate() {
-                let mut ray = Ray::new(camera.position, Vec3::ZERO);
-                ray_ss_coords.x = self.get_ray_world_coord_x(x as u32, scale_factor);
+    /// Applies the display transform to the framebuffer, giving `0x00RRGGBB` pixels. AOVs that
+    /// don't hold light are shown as false colors instead.
+    pub fn write_display_pixels(&self, pixel_data: &mut [u32]) {
+        pixel_data.par_iter_mut().zip(self.framebuffer.par_iter()).for_each(|(pixel, color)| {
+            *pixel = if self.aov.is_radiance() {
+                self.apply_display_transform(*color)
+            } else {
+                let color = self.aov.visualize(*color);
+                Self::to_u32_rgb(color.x, color.y, color.z)
+            };
+        });
+    }
 
-                let pixel =
-                    *camera_look_at * Vec4::new(ray_ss_coords.x, ray_ss_coords.y, -1.0, 1.0);
+    pub const fn get_aov(&self) -> Aov {
+        self.aov
+    }
 
-                ray.direction = (pixel.truncate() - ray.origin).normalize();
+    pub fn set_aov(&mut self, aov: Aov) {
+        self.aov = aov;
+        self.reset_accumulation();
+    }
 
-                let mut final_color: RGBColor =
-                    self.calculate_color(scenegraph, lights, 0, &mut ray);
-                final_color.max_to_one();
+    pub fn toggle_aov(&mut self) {
+        self.set_aov(self.aov.next());
+        println!("AOV: {}", self.aov);
+    }
 
-                let final_color = Self::to_u32_rgb(final_color.x, final_color.y, final_color.z);
-                *pixel_data = final_color;
+    fn render_whitted(
+        &self,
+        framebuffer: &mut [RGBColor],
+        scenegraph: &Scenegraph<'_>,
+        camera: &Camera,
+        lights: &Vec<LightEnum>,
+    ) {
+        // We are grabbing a parallel iterator over rows
+        framebuffer.par_chunks_mut(self.width as usize).enumerate().for_each(|(y, row)| {
+            for (x, pixel_color) in row.iter_mut().enumerate() {
+                let pixel_center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                // Used for the pixel samples and the shadow rays towards area lights
+                let mut sampler = Sampler::new((y * self.width as usize + x) as u32, 0);
+
+                let pixel_sample_count = self.pixel_samples_per_axis * self.pixel_samples_per_axis;
+                let final_color = if pixel_sample_count == 1 {
+                    self.get_primary_ray(camera, pixel_center.x, pixel_center.y, &mut sampler)
+                        .map_or(RGBColor::ZERO, |mut ray| {
+                            self.trace_aov(scenegraph, lights, &mut ray, &mut sampler)
+                        })
+                } else {
+                    let mut weighted_color = RGBColor::ZERO;
+                    let mut total_weight = 0.0;
+                    for stratum in 0..pixel_sample_count {
+                        let (offset, weight) =
+                            self.get_pixel_sample_offset(stratum, sampler.next_vec2());
+                        if weight == 0.0 {
+                            continue;
+                        }
+                        let position = pixel_center + offset;
+                        // Samples outside of the projection count as black
+                        if let Some(mut ray) =
+                            self.get_primary_ray(camera, position.x, position.y, &mut sampler)
+                        {
+                            weighted_color +=
+                                weight * self.trace_aov(scenegraph, lights, &mut ray, &mut sampler);
+                        }
+                        total_weight += weight;
+                    }
+                    if total_weight > 0.0 {
+                        weighted_color / total_weight
+                    } else {
+                        RGBColor::ZERO
+                    }
+                };
+                *pixel_color = final_color;
             }
         });
     }
 
-    fn to_u32_rgb(r: f32, g: f32, b: f32) -> u32 {
-        let ri = (r * 255.0) as u32;
-        let gi = (g * 255.0) as u32;
-        let bi = (b * 255.0) as u32;
+    /// Adds one path traced sample to every pixel and stores the running average. The average
+    /// starts over whenever the camera, the lights or the objects changed since the previous
+    /// frame.
+    fn render_path_traced(
+        &mut self,
+        framebuffer: &mut [RGBColor],
+        scenegraph: &Scenegraph<'_>,
+        camera: &Camera,
+        light_manager: &LightManager<LightEnum>,
+    ) {
+        let revisions =
+            (camera.get_revision(), light_manager.get_revision(), scenegraph.get_revision());
+        if self.accumulation_revisions != Some(revisions) {
+            self.reset_accumulation();
+            self.accumulation_revisions = Some(revisions);
+        }
+
+        self.accumulated_samples += 1;
+        let sample_index = self.accumulated_samples;
+        // Consecutive frames visit the strata of the pixel in turn
+        let stratum =
+            (sample_index - 1) % (self.pixel_samples_per_axis * self.pixel_samples_per_axis);
+        let lights = light_manager.get_lights();
+        let width = self.width as usize;
+
+        let mut accumulation_buffer = std::mem::take(&mut self.accumulation_buffer);
+        let mut accumulation_weights = std::mem::take(&mut self.accumulation_weights);
+
+        framebuffer
+            .par_chunks_mut(width)
+            .zip(accumulation_buffer.par_chunks_mut(width))
+            .zip(accumulation_weights.par_chunks_mut(width))
+            .enumerate()
+            .for_each(|(y, ((row, accumulation_row), weight_row))| {
+                for (x, ((pixel_color, accumulated_color), accumulated_weight)) in row
+                    .iter_mut()
+                    .zip(accumulation_row.iter_mut())
+                    .zip(weight_row.iter_mut())
+                    .enumerate()
+                {
+                    let mut sampler = Sampler::new((y * width + x) as u32, sample_index);
+                    let (offset, weight) =
+                        self.get_pixel_sample_offset(stratum, sampler.next_vec2());
+                    let position = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) + offset;
+                    let sample_color = self
+                        .get_primary_ray(camera, position.x, position.y, &mut sampler)
+                        .map_or(RGBColor::ZERO, |mut ray| {
+                            self.trace_aov(scenegraph, lights, &mut ray, &mut sampler)
+                        });
+                    // A single NaN would otherwise stay in the pixel until the next reset
+                    if sample_color.is_finite() {
+                        *accumulated_color += sample_color * weight;
+                        *accumulated_weight += weight;
+                    }
+
+                    *pixel_color = if *accumulated_weight > 0.0 {
+                        *accumulated_color / *accumulated_weight
+                    } else {
+                        RGBColor::ZERO
+                    };
+                }
+            });
+
+        self.accumulation_buffer = accumulation_buffer;
+        self.accumulation_weights = accumulation_weights;
+    }
+
+    /// Offset from the pixel center of a jittered sample in one cell of a grid spanning the
+    /// support of the pixel filter, and the weight of the sample.
+    fn get_pixel_sample_offset(&self, stratum: u32, random: Vec2) -> (Vec2, f32) {
+        let samples_per_axis = self.pixel_samples_per_axis;
+        let cell =
+            Vec2::new((stratum % samples_per_axis) as f32, (stratum / samples_per_axis) as f32);
+        let position = (cell + random) / samples_per_axis as f32;
+        let offset = (position * 2.0 - Vec2::ONE) * self.pixel_filter.get_radius();
+        (offset, self.pixel_filter.evaluate(offset))
+    }
+
+    /// Camera ray through a point of the image given in (fractional) pixel coordinates, `None`
+    /// where the projection of the camera doesn't cover the image. With an open aperture the ray
+    /// starts on a random point of the thin lens and passes through the point of the focal plane
+    /// the pinhole ray would have hit, which blurs everything else.
+    fn get_primary_ray(
+        &self,
+        camera: &Camera,
+        x: f32,
+        y: f32,
+        sampler: &mut Sampler,
+    ) -> Option<Ray> {
+        let image_position = Vec2::new(
+            2.0f32.mul_add(x / self.width as f32, -1.0),
+            2.0f32.mul_add(-y / self.height as f32, 1.0),
+        );
+        let projection = camera.get_projection();
+        let (mut origin, mut direction) = projection.get_local_ray(
+            image_position,
+            self.aspect_ratio,
+            camera.get_scale_factor(),
+            camera.get_orthographic_height(),
+            camera.get_fisheye_fov_radians(),
+        )?;
+
+        let aperture_radius = camera.get_aperture_radius();
+        if aperture_radius > 0.0 && projection.is_planar() {
+            // Both the lens and the focal plane are perpendicular to the view direction
+            let focus_point = origin + direction * (camera.get_focal_distance() / -direction.z);
+            origin += (uniform_sample_disk(sampler.next_vec2()) * aperture_radius).extend(0.0);
+            direction = focus_point - origin;
+        }
+
+        let origin = camera.look_at * origin.extend(1.0);
+        let direction = camera.look_at * direction.extend(0.0);
+        Some(Ray::new(origin.truncate(), direction.truncate().normalize()))
+    }
+
+    /// Value of the selected AOV seen along a camera ray.
+    fn trace_aov(
+        &self,
+        scenegraph: &Scenegraph<'_>,
+        lights: &Vec<LightEnum>,
+        ray: &mut Ray,
+        sampler: &mut Sampler,
+    ) -> RGBColor {
+        if self.aov.is_radiance() {
+            let radiance = match self.integrator {
+                Integrator::Whitted => self.calculate_color(scenegraph, lights, 0, ray, sampler),
+                Integrator::PathTracer => self.trace_path(scenegraph, lights, ray, sampler),
+            };
+            return match self.aov {
+                Aov::Direct => radiance.direct,
+                Aov::Indirect => radiance.indirect,
+                _ => radiance.get_total(),
+            };
+        }
+
+        let mut hit_record = HitRecord::default();
+        if !scenegraph.hit(ray, &mut hit_record, false) {
+            return RGBColor::ZERO;
+        }
+        let Some(material) = hit_record.material else {
+            return RGBColor::ZERO;
+        };
+        if hit_record.normal.dot(ray.direction) > 0.0 {
+            hit_record.normal = -hit_record.normal;
+        }
+
+        match self.aov {
+            Aov::Depth => RGBColor::splat(hit_record.t),
+            Aov::Normal => hit_record.normal,
+            Aov::Albedo => material.get_albedo(&hit_record),
+            Aov::ObjectId => RGBColor::splat(hit_record.object_id as f32),
+            Aov::MaterialId => RGBColor::splat(material.get_id() as f32),
+            Aov::Uv => hit_record.uv.extend(0.0),
+            Aov::ShadowMask => RGBColor::splat(self.calculate_shadow_mask(
+                scenegraph,
+                lights,
+                &hit_record,
+                ray,
+                sampler,
+            )),
+            Aov::Beauty | Aov::Direct | Aov::Indirect => RGBColor::ZERO,
+        }
+    }
+
+    /// Turns linear radiance into a displayable pixel: exposure, tone mapping and sRGB encoding.
+    fn apply_display_transform(&self, color: RGBColor) -> u32 {
+        let mut color = self.tone_mapper.apply(color * self.exposure.exp2());
+        if self.is_srgb_enabled {
+            color = RGBColor::from_array(color.to_array().map(linear_to_srgb));
+        }
+        Self::to_u32_rgb(color.x, color.y, color.z)
+    }
+
+    const fn to_u32_rgb(r: f32, g: f32, b: f32) -> u32 {
+        let ri = r.mul_add(255.0, 0.5) as u32;
+        let gi = g.mul_add(255.0, 0.5) as u32;
+        let bi = b.mul_add(255.0, 0.5) as u32;
 
         (ri << 16) | (gi << 8) | bi
     }
@@ -83,127 +404,416 @@ impl Renderer {
         lights: &Vec<LightEnum>,
         mut current_amount_bounces: u32,
         ray: &mut Ray,
-    ) -> RGBColor {
-        let mut accumulated_color = RGBColor::ZERO;
+        sampler: &mut Sampler,
+    ) -> Radiance {
+        let mut accumulated_color = Radiance::default();
+        let first_bounce = current_amount_bounces;
         let mut reflectiveness_env_mat_first_hit = 1.0;
         let mut previous_reflectivness = 1.0;
         let mut previous_lambert = 1.0;
 
         while current_amount_bounces < self.max_bounces {
-            let mut bounce_color = RGBColor::ZERO;
-
+            let is_first_hit = current_amount_bounces == first_bounce;
             let mut hit_record = HitRecord::default();
-            if !scenegraph.hit(ray, &mut hit_record, false) {
+            let is_hit = scenegraph.hit(ray, &mut hit_record, false);
+
+            let closest_t = if is_hit { hit_record.t } else { f32::INFINITY };
+            if let Some(emitted_radiance) = Self::get_visible_emitter(lights, ray, closest_t) {
+                accumulated_color.add(
+                    is_first_hit,
+                    emitted_radiance * previous_reflectivness * previous_lambert,
+                );
+                break;
+            }
+
+            if !is_hit {
                 break;
             }
 
             if current_amount_bounces == 0 {
                 reflectiveness_env_mat_first_hit = match hit_record.material {
-                    Some(_) => hit_record.material.unwrap().get_reflectiveness_environment(),
-                    None => return RGBColor::ZERO,
+                    Some(_) => {
+                        hit_record.material.unwrap().get_reflectiveness_environment(&hit_record)
+                    }
+                    None => return Radiance::default(),
                 };
             }
 
-            let mut lambert_cosine_law;
-            let offset = 0.0001;
+            let mut bounce_color =
+                self.calculate_direct_lighting(scenegraph, lights, &hit_record, ray, sampler);
 
-            let mut ray_hit_to_light = Ray {
-                origin: hit_record.hitpoint + (hit_record.normal * offset),
-                ..Default::default()
-            };
+            let material = hit_record.material.unwrap();
 
-            for light in lights {
-                if !light.is_light_enabled() {
-                    continue;
+            if let Some(index_of_refraction) = material.get_index_of_refraction() {
+                let mut dielectric_color = self.calculate_dielectric_color(
+                    scenegraph,
+                    lights,
+                    current_amount_bounces,
+                    ray,
+                    &hit_record,
+                    index_of_refraction,
+                    sampler,
+                );
+
+                // Leaving the object means this ray travelled through the medium to get here
+                if ray.direction.dot(hit_record.normal) > 0.0 {
+                    let transmittance = (-material.get_absorption() * hit_record.t).exp();
+                    bounce_color *= transmittance;
+                    dielectric_color *= transmittance;
                 }
 
-                if self.are_hard_shadows_enabled {
-                    let mut direction_magnitude_returned = 0.0;
+                let weight = previous_reflectivness * previous_lambert;
+                accumulated_color.add(is_first_hit, bounce_color * weight);
+                accumulated_color.add(false, dielectric_color * weight);
+                break;
+            }
 
-                    ray_hit_to_light.direction = light.get_direction_magnitude(
-                        &hit_record.hitpoint,
-                        &mut direction_magnitude_returned,
-                    );
+            accumulated_color
+                .add(is_first_hit, bounce_color * previous_reflectivness * previous_lambert);
+
+            if material.get_reflectiveness_environment(&hit_record).eq(&0.0) {
+                break;
+            }
+
+            let reflect = reflect(ray.direction, hit_record.normal);
+
+            let lambert_cosine_law = hit_record.normal.dot(reflect);
+
+            ray.direction = reflect.normalize();
+            ray.origin = hit_record.hitpoint;
+
+            previous_reflectivness = reflectiveness_env_mat_first_hit;
+            previous_lambert = lambert_cosine_law;
+
+            current_amount_bounces += 1;
+        }
+        //accumulated_color = accumulated_color / lights.len() as f32;
+        accumulated_color
+    }
+
+    fn calculate_direct_lighting(
+        &self,
+        scenegraph: &Scenegraph<'_>,
+        lights: &Vec<LightEnum>,
+        hit_record: &HitRecord<'_>,
+        ray: &Ray,
+        sampler: &mut Sampler,
+    ) -> RGBColor {
+        self.sum_direct_lighting(
+            scenegraph,
+            lights,
+            hit_record,
+            ray,
+            sampler,
+            self.are_hard_shadows_enabled,
+        )
+    }
 
+    /// Sums the light arriving directly from every enabled light that isn't blocked by an object.
+    /// Area lights are sampled with a stratified grid of shadow rays, which softens their shadows.
+    fn sum_direct_lighting(
+        &self,
+        scenegraph: &Scenegraph<'_>,
+        lights: &Vec<LightEnum>,
+        hit_record: &HitRecord<'_>,
+        ray: &Ray,
+        sampler: &mut Sampler,
+        are_shadows_enabled: bool,
+    ) -> RGBColor {
+        let mut direct_color = RGBColor::ZERO;
+        let offset = 0.0001;
+
+        let mut ray_hit_to_light = Ray {
+            origin: hit_record.hitpoint + (hit_record.normal * offset),
+            ..Default::default()
+        };
+
+        for light in lights {
+            if !light.is_light_enabled() {
+                continue;
+            }
+
+            // The path tracer already averages many frames, one shadow ray per light is enough
+            let samples_per_axis = match self.integrator {
+                Integrator::Whitted => light.get_samples_per_axis(),
+                Integrator::PathTracer => 1,
+            };
+            let mut light_color = RGBColor::ZERO;
+
+            for i in 0..samples_per_axis * samples_per_axis {
+                let stratum =
+                    Vec2::new((i % samples_per_axis) as f32, (i / samples_per_axis) as f32);
+                let light_sample = light.sample(
+                    &hit_record.hitpoint,
+                    (stratum + sampler.next_vec2()) / samples_per_axis as f32,
+                );
+
+                let lambert_cosine_law = hit_record.normal.dot(light_sample.direction);
+                if lambert_cosine_law < 0.0 {
+                    continue;
+                }
+
+                if are_shadows_enabled {
+                    ray_hit_to_light.direction = light_sample.direction;
                     ray_hit_to_light.t_min = 0.0001;
-                    ray_hit_to_light.t_max = direction_magnitude_returned;
+                    ray_hit_to_light.t_max = light_sample.distance;
                     let mut hit_record_shadow = HitRecord::default();
                     if scenegraph.hit(&mut ray_hit_to_light, &mut hit_record_shadow, true) {
                         continue;
                     }
                 }
 
-                lambert_cosine_law =
-                    hit_record.normal.dot(light.get_direction(&hit_record.hitpoint));
-
-                if lambert_cosine_law < 0.0 {
-                    continue;
-                }
-
-                bounce_color += self.get_color_mode_according_to_render_mode(
-                    light,
+                light_color += self.get_color_mode_according_to_render_mode(
+                    &light_sample,
                     lambert_cosine_law,
-                    &hit_record,
+                    hit_record,
                     ray,
                 );
             }
 
-            if hit_record.material.unwrap().get_reflectiveness_environment().eq(&0.0) {
+            direct_color += light_color / (samples_per_axis * samples_per_axis) as f32;
+        }
+
+        direct_color
+    }
+
+    /// Share of the direct light at the hitpoint that other objects block, 0 where no light
+    /// reaches the surface at all. Both sums see the same light samples.
+    fn calculate_shadow_mask(
+        &self,
+        scenegraph: &Scenegraph<'_>,
+        lights: &Vec<LightEnum>,
+        hit_record: &HitRecord<'_>,
+        ray: &Ray,
+        sampler: &mut Sampler,
+    ) -> f32 {
+        let mut unshadowed_sampler = sampler.clone();
+        let unshadowed = self
+            .sum_direct_lighting(
+                scenegraph,
+                lights,
+                hit_record,
+                ray,
+                &mut unshadowed_sampler,
+                false,
+            )
+            .luminance();
+        if unshadowed <= 0.0 {
+            return 0.0;
+        }
+
+        let shadowed = self
+            .sum_direct_lighting(scenegraph, lights, hit_record, ray, sampler, true)
+            .luminance();
+        (1.0 - shadowed / unshadowed).clamp(0.0, 1.0)
+    }
+
+    /// Radiance of the closest enabled light surface `ray` hits before `t_max`.
+    fn get_visible_emitter(lights: &[LightEnum], ray: &Ray, t_max: f32) -> Option<RGBColor> {
+        lights
+            .iter()
+            .filter(|light| light.is_light_enabled())
+            .filter_map(|light| light.hit_emitter(ray))
+            .filter(|(t, _)| *t < t_max)
+            .min_by(|(t, _), (other_t, _)| t.total_cmp(other_t))
+            .map(|(_, emitted_radiance)| emitted_radiance)
+    }
+
+    /// Monte Carlo estimate of the light arriving along `ray`. Every vertex of the path samples the
+    /// lights directly (next-event estimation) and continues in a direction importance sampled from
+    /// its BRDF, Russian roulette ends paths that carry little energy.
+    fn trace_path(
+        &self,
+        scenegraph: &Scenegraph<'_>,
+        lights: &Vec<LightEnum>,
+        ray: &mut Ray,
+        sampler: &mut Sampler,
+    ) -> Radiance {
+        let mut radiance = Radiance::default();
+        let mut throughput = RGBColor::ONE;
+        let offset = 0.0001;
+        // Light surfaces reached after a diffuse or glossy bounce were already sampled directly
+        let mut is_specular_path = true;
+
+        for bounce in 0..Self::MAX_PATH_BOUNCES {
+            let mut hit_record = HitRecord::default();
+            let is_hit = scenegraph.hit(ray, &mut hit_record, false);
+
+            let closest_t = if is_hit { hit_record.t } else { f32::INFINITY };
+            if let Some(emitted_radiance) = Self::get_visible_emitter(lights, ray, closest_t) {
+                if is_specular_path {
+                    radiance.add(bounce == 0, throughput * emitted_radiance);
+                }
                 break;
             }
 
-            let reflect =
-                ray.direction - hit_record.normal * (ray.direction.dot(hit_record.normal) * 2.0);
+            if !is_hit {
+                break;
+            }
+            let Some(material) = hit_record.material else {
+                break;
+            };
 
-            lambert_cosine_law = hit_record.normal.dot(reflect);
+            if let Some(index_of_refraction) = material.get_index_of_refraction() {
+                // Perfectly specular, so the lights can't be sampled and the path either reflects
+                // or refracts with the Fresnel reflectance as the probability of reflecting
+                let is_entering = ray.direction.dot(hit_record.normal) < 0.0;
+                if !is_entering {
+                    throughput *= (-material.get_absorption() * hit_record.t).exp();
+                }
+                let (normal, eta) = if is_entering {
+                    (hit_record.normal, 1.0 / index_of_refraction)
+                } else {
+                    (-hit_record.normal, index_of_refraction)
+                };
 
-            ray.direction = reflect.normalize();
-            ray.origin = hit_record.hitpoint;
+                let refracted_direction = refract(ray.direction, normal, eta);
+                let fresnel = refracted_direction
+                    .map_or(1.0, |_| brdf::fresnel_dielectric(-ray.direction.dot(normal), eta));
 
-            accumulated_color += bounce_color * previous_reflectivness * previous_lambert;
+                *ray = match refracted_direction {
+                    Some(refracted_direction) if sampler.next_f32() >= fresnel => {
+                        Ray::new(hit_record.hitpoint - normal * offset, refracted_direction)
+                    }
+                    _ => Ray::new(
+                        hit_record.hitpoint + normal * offset,
+                        reflect(ray.direction, normal).normalize(),
+                    ),
+                };
+                is_specular_path = true;
+            } else {
+                // Shade the side of the surface the ray arrived from
+                if hit_record.normal.dot(ray.direction) > 0.0 {
+                    hit_record.normal = -hit_record.normal;
+                }
 
-            previous_reflectivness = reflectiveness_env_mat_first_hit;
-            previous_lambert = lambert_cosine_law;
+                radiance.add(
+                    bounce == 0,
+                    throughput
+                        * self.calculate_direct_lighting(
+                            scenegraph,
+                            lights,
+                            &hit_record,
+                            ray,
+                            sampler,
+                        ),
+                );
 
-            current_amount_bounces += 1;
+                // Like the Whitted bounces, reflective materials mirror part of the indirect light
+                if sampler.next_f32() < material.get_reflectiveness_environment(&hit_record) {
+                    *ray = Ray::new(
+                        hit_record.hitpoint + hit_record.normal * offset,
+                        reflect(ray.direction, hit_record.normal).normalize(),
+                    );
+                    is_specular_path = true;
+                } else {
+                    let Some(brdf_sample) =
+                        material.sample_brdf(&hit_record, &-ray.direction, sampler)
+                    else {
+                        break;
+                    };
+                    throughput *= brdf_sample.weight;
+                    is_specular_path = false;
+                    *ray = Ray::new(
+                        hit_record.hitpoint + hit_record.normal * offset,
+                        brdf_sample.direction,
+                    );
+                }
+            }
+
+            if bounce >= Self::RUSSIAN_ROULETTE_START_BOUNCE {
+                let survival_probability = throughput.max_element().min(0.95);
+                if sampler.next_f32() >= survival_probability {
+                    break;
+                }
+                throughput /= survival_probability;
+            }
         }
-        //accumulated_color = accumulated_color / lights.len() as f32;
-        accumulated_color
-    }
 
-    fn get_ray_world_coord_x(&self, x: u32, scale_factor: f32) -> f32 {
-        2.0f32.mul_add((x as f32 + 0.5) / self.width as f32, -1.0)
-            * self.aspect_ratio
-            * scale_factor
+        radiance
     }
 
-    fn get_ray_world_coord_y(&self, y: u32, scale_factor: f32) -> f32 {
-        2.0f32.mul_add(-(y as f32 + 0.5) / self.height as f32, 1.0) * scale_factor
+    /// Splits the ray hitting a refractive surface into a reflected and a transmitted ray,
+    /// weighted by the Fresnel reflectance. Total internal reflection only follows the reflected ray.
+    #[allow(clippy::too_many_arguments)]
+    fn calculate_dielectric_color(
+        &self,
+        scenegraph: &Scenegraph<'_>,
+        lights: &Vec<LightEnum>,
+        current_amount_bounces: u32,
+        ray: &Ray,
+        hit_record: &HitRecord<'_>,
+        index_of_refraction: f32,
+        sampler: &mut Sampler,
+    ) -> RGBColor {
+        let offset = 0.0001;
+        let is_entering = ray.direction.dot(hit_record.normal) < 0.0;
+        let (normal, eta) = if is_entering {
+            (hit_record.normal, 1.0 / index_of_refraction)
+        } else {
+            (-hit_record.normal, index_of_refraction)
+        };
+
+        let refracted_direction = refract(ray.direction, normal, eta);
+        let fresnel = refracted_direction
+            .map_or(1.0, |_| brdf::fresnel_dielectric(-ray.direction.dot(normal), eta));
+
+        let mut reflected_ray = Ray::new(
+            hit_record.hitpoint + normal * offset,
+            reflect(ray.direction, normal).normalize(),
+        );
+        let mut color = fresnel
+            * self
+                .calculate_color(
+                    scenegraph,
+                    lights,
+                    current_amount_bounces + 1,
+                    &mut reflected_ray,
+                    sampler,
+                )
+                .get_total();
+
+        if let Some(refracted_direction) = refracted_direction {
+            let mut refracted_ray =
+                Ray::new(hit_record.hitpoint - normal * offset, refracted_direction);
+            color += (1.0 - fresnel)
+                * self
+                    .calculate_color(
+                        scenegraph,
+                        lights,
+                        current_amount_bounces + 1,
+                        &mut refracted_ray,
+                        sampler,
+                    )
+                    .get_total();
+        }
+
+        color
     }
 
     fn get_color_mode_according_to_render_mode(
         &self,
-        light: &LightEnum,
+        light_sample: &LightSample,
         lambert_cosine_law: f32,
         hit_record: &HitRecord<'_>,
         ray: &Ray,
     ) -> RGBColor {
         match self.render_mode {
             0 => {
-                light.get_bi_radians(&hit_record.hitpoint)
+                light_sample.bi_radiance
                     * lambert_cosine_law
                     * hit_record.material.unwrap().shade(
                         hit_record,
-                        &light.get_direction(&hit_record.hitpoint),
+                        &light_sample.direction,
                         &(-1.0 * ray.direction),
                     )
             }
-            1 => light.get_bi_radians(&hit_record.hitpoint) * lambert_cosine_law,
+            1 => light_sample.bi_radiance * lambert_cosine_law,
             2 => {
                 lambert_cosine_law
                     * hit_record.material.unwrap().shade(
                         hit_record,
-                        &light.get_direction(&hit_record.hitpoint),
+                        &light_sample.direction,
                         &(-1.0 * ray.direction),
                     )
             }
@@ -219,11 +829,69 @@ impl Renderer {
         println!("\n\n current max bounces: {}", self.max_bounces);
     }
 
+    pub fn toggle_pixel_samples(&mut self) {
+        self.pixel_samples_per_axis += 1;
+        if self.pixel_samples_per_axis > Self::MAX_PIXEL_SAMPLES_PER_AXIS {
+            self.pixel_samples_per_axis = 1;
+        }
+        self.reset_accumulation();
+        println!(
+            "\n\n current samples per pixel: {}",
+            self.pixel_samples_per_axis * self.pixel_samples_per_axis
+        );
+    }
+
+    pub fn toggle_pixel_filter(&mut self) {
+        self.pixel_filter = self.pixel_filter.next();
+        self.reset_accumulation();
+        println!("Pixel filter: {}", self.pixel_filter);
+    }
+
+    /// Only changes how the image is displayed, path traced samples are kept.
+    pub fn change_exposure(&mut self, stops: f32) {
+        self.exposure += stops;
+        println!("Exposure: {:+.1} stops", self.exposure);
+    }
+
+    pub fn toggle_tone_mapper(&mut self) {
+        self.tone_mapper = self.tone_mapper.next();
+        println!("Tone mapper: {}", self.tone_mapper);
+    }
+
+    pub fn toggle_srgb(&mut self) {
+        self.is_srgb_enabled = !self.is_srgb_enabled;
+        println!("Output: {}", if self.is_srgb_enabled { "sRGB" } else { "linear" });
+    }
+
     pub fn toggle_shadows(&mut self) {
         self.are_hard_shadows_enabled = !self.are_hard_shadows_enabled;
+        self.reset_accumulation();
+    }
+
+    pub fn toggle_integrator(&mut self) {
+        self.integrator = match self.integrator {
+            Integrator::Whitted => {
+                println!("Integrator: path tracer");
+                Integrator::PathTracer
+            }
+            Integrator::PathTracer => {
+                println!("Integrator: Whitted");
+                Integrator::Whitted
+            }
+        };
+        self.reset_accumulation();
+    }
+
+    /// Throws away the path traced samples, for changes the renderer can't detect by itself such
+    /// as switching scenes.
+    pub fn reset_accumulation(&mut self) {
+        self.accumulation_buffer.fill(RGBColor::ZERO);
+        self.accumulation_weights.fill(0.0);
+        self.accumulated_samples = 0;
     }
 
     pub fn toggle_render_mode(&mut self) {
+        self.reset_accumulation();
         self.render_mode += 1;
 
         match self.render_mode {
@@ -0,0 +1,35 @@
+use super::texture_properties::{RGBColor, Texture, TextureProperties};
+use glam::Vec2;
+
+/// Checkerboard with squares of one unit of texture coordinates, scale it to get more squares.
+pub struct CheckerTexture {
+    pub properties: TextureProperties,
+    pub even_color: RGBColor,
+    pub odd_color: RGBColor,
+}
+
+impl CheckerTexture {
+    pub const fn new(
+        properties: TextureProperties,
+        even_color: RGBColor,
+        odd_color: RGBColor,
+    ) -> Self {
+        Self { properties, even_color, odd_color }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn get_properties(&self) -> &TextureProperties {
+        &self.properties
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn evaluate(&self, uv: Vec2) -> RGBColor {
+        let square = uv.floor();
+        if (square.x as i64 + square.y as i64) % 2 == 0 {
+            self.even_color
+        } else {
+            self.odd_color
+        }
+    }
+}
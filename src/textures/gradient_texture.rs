@@ -0,0 +1,48 @@
+use super::texture_properties::{RGBColor, Texture, TextureProperties, WrapMode};
+use glam::Vec2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GradientType {
+    /// From u = 0 to u = 1
+    Horizontal,
+    /// From v = 0 to v = 1
+    Vertical,
+    /// From the center of the texture out to its edges
+    Radial,
+}
+
+/// Linear blend between two colors, repeating outside of [0, 1] according to the wrap mode.
+pub struct GradientTexture {
+    pub properties: TextureProperties,
+    pub gradient_type: GradientType,
+    pub start_color: RGBColor,
+    pub end_color: RGBColor,
+    pub wrap_mode: WrapMode,
+}
+
+impl GradientTexture {
+    pub const fn new(
+        properties: TextureProperties,
+        gradient_type: GradientType,
+        start_color: RGBColor,
+        end_color: RGBColor,
+        wrap_mode: WrapMode,
+    ) -> Self {
+        Self { properties, gradient_type, start_color, end_color, wrap_mode }
+    }
+}
+
+impl Texture for GradientTexture {
+    fn get_properties(&self) -> &TextureProperties {
+        &self.properties
+    }
+
+    fn evaluate(&self, uv: Vec2) -> RGBColor {
+        let position = match self.gradient_type {
+            GradientType::Horizontal => uv.x,
+            GradientType::Vertical => uv.y,
+            GradientType::Radial => uv.distance(Vec2::splat(0.5)) * 2.0,
+        };
+        self.start_color.lerp(self.end_color, self.wrap_mode.wrap(position))
+    }
+}
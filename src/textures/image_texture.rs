@@ -0,0 +1,79 @@
+use super::texture_properties::{RGBColor, Texture, TextureProperties, WrapMode};
+use crate::{image_reader::HdrImage, math::tone_mapping::srgb_to_linear};
+use glam::Vec2;
+
+/// How texels are looked up between their centers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureFilter {
+    /// Takes the closest texel, keeps the hard edges of pixel art
+    Nearest,
+    /// Blends the four surrounding texels
+    Bilinear,
+}
+
+/// Texture read from an image. The bottom row of the image is at v = 0, like in OBJ files.
+pub struct ImageTexture {
+    pub properties: TextureProperties,
+    image: HdrImage,
+    pub wrap_mode: WrapMode,
+    pub filter: TextureFilter,
+}
+
+impl ImageTexture {
+    /// Color images are usually stored sRGB encoded, they are converted to linear once here.
+    /// Images holding data such as roughness are linear already.
+    pub fn new(
+        properties: TextureProperties,
+        mut image: HdrImage,
+        is_srgb: bool,
+        wrap_mode: WrapMode,
+        filter: TextureFilter,
+    ) -> Self {
+        if is_srgb {
+            for pixel in &mut image.pixels {
+                *pixel = RGBColor::from_array(pixel.to_array().map(srgb_to_linear));
+            }
+        }
+        Self { properties, image, wrap_mode, filter }
+    }
+
+    fn get_texel(&self, x: i32, y: i32) -> RGBColor {
+        self.image.get_pixel(
+            self.wrap_mode.wrap_index(x, self.image.width),
+            self.wrap_mode.wrap_index(y, self.image.height),
+        )
+    }
+}
+
+impl Texture for ImageTexture {
+    fn get_properties(&self) -> &TextureProperties {
+        &self.properties
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_precision_loss)]
+    fn evaluate(&self, uv: Vec2) -> RGBColor {
+        let size = Vec2::new(self.image.width as f32, self.image.height as f32);
+        // Rows run from top to bottom, v from bottom to top
+        let position = Vec2::new(uv.x, 1.0 - uv.y) * size;
+
+        match self.filter {
+            TextureFilter::Nearest => {
+                let texel = position.floor();
+                self.get_texel(texel.x as i32, texel.y as i32)
+            }
+            TextureFilter::Bilinear => {
+                // Texel centers sit at half coordinates
+                let position = position - 0.5;
+                let texel = position.floor();
+                let weight = position - texel;
+                let (left, top) = (texel.x as i32, texel.y as i32);
+
+                let upper = self.get_texel(left, top).lerp(self.get_texel(left + 1, top), weight.x);
+                let lower =
+                    self.get_texel(left, top + 1).lerp(self.get_texel(left + 1, top + 1), weight.x);
+                upper.lerp(lower, weight.y)
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+pub mod checker_texture;
+pub mod gradient_texture;
+pub mod image_texture;
+pub mod noise_texture;
+pub mod texture_properties;
+
+pub use checker_texture::CheckerTexture;
+use enum_dispatch::enum_dispatch;
+use glam::{Vec2, Vec3};
+pub use gradient_texture::{GradientTexture, GradientType};
+pub use image_texture::{ImageTexture, TextureFilter};
+pub use noise_texture::{NoiseTexture, NoiseType};
+pub use texture_properties::{Texture, TextureProperties, WrapMode};
+pub type RGBColor = Vec3;
+
+#[enum_dispatch(Texture)]
+pub enum TextureEnum {
+    Image(ImageTexture),
+    Checker(CheckerTexture),
+    Noise(NoiseTexture),
+    Gradient(GradientTexture),
+}
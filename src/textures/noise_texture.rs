@@ -0,0 +1,53 @@
+use super::texture_properties::{RGBColor, Texture, TextureProperties};
+use crate::math::noise;
+use glam::Vec2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NoiseType {
+    /// Smooth clouds, marble and wood like variation
+    Perlin,
+    /// Cells like stones, scales or foam
+    Worley,
+}
+
+/// Blends between two colors by a fractal noise pattern, one unit of texture coordinates holds
+/// one noise cell of the coarsest octave.
+pub struct NoiseTexture {
+    pub properties: TextureProperties,
+    pub noise_type: NoiseType,
+    pub low_color: RGBColor,
+    pub high_color: RGBColor,
+    /// Layers of ever finer detail
+    pub octaves: u32,
+    /// Different seeds give different patterns
+    pub seed: u32,
+}
+
+impl NoiseTexture {
+    pub const fn new(
+        properties: TextureProperties,
+        noise_type: NoiseType,
+        low_color: RGBColor,
+        high_color: RGBColor,
+        octaves: u32,
+        seed: u32,
+    ) -> Self {
+        Self { properties, noise_type, low_color, high_color, octaves, seed }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn get_properties(&self) -> &TextureProperties {
+        &self.properties
+    }
+
+    fn evaluate(&self, uv: Vec2) -> RGBColor {
+        let value = match self.noise_type {
+            NoiseType::Perlin => {
+                noise::fractal(noise::perlin, uv, self.octaves, self.seed).mul_add(0.5, 0.5)
+            }
+            NoiseType::Worley => noise::fractal(noise::worley, uv, self.octaves, self.seed),
+        };
+        self.low_color.lerp(self.high_color, value.clamp(0.0, 1.0))
+    }
+}
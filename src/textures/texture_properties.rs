@@ -0,0 +1,93 @@
+use enum_dispatch::enum_dispatch;
+use glam::{Vec2, Vec3};
+pub type RGBColor = Vec3;
+
+/// Placement of a texture on the surface. A scale of 4 repeats a texture four times over the
+/// texture coordinates of a primitive.
+#[derive(Clone, Copy)]
+pub struct TextureProperties {
+    pub scale: Vec2,
+    pub offset: Vec2,
+}
+
+impl TextureProperties {
+    pub const fn new(scale: Vec2, offset: Vec2) -> Self {
+        Self { scale, offset }
+    }
+
+    pub fn transform_uv(&self, uv: Vec2) -> Vec2 {
+        uv * self.scale + self.offset
+    }
+}
+
+impl Default for TextureProperties {
+    fn default() -> Self {
+        Self::new(Vec2::ONE, Vec2::ZERO)
+    }
+}
+
+/// What a texture shows outside of the [0, 1] range of its texture coordinates.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WrapMode {
+    Repeat,
+    /// Repeats with every other copy flipped, so there are no seams
+    MirroredRepeat,
+    /// Stretches the border
+    Clamp,
+}
+
+impl WrapMode {
+    /// Maps a texture coordinate into [0, 1].
+    pub fn wrap(self, coordinate: f32) -> f32 {
+        match self {
+            Self::Repeat => coordinate - coordinate.floor(),
+            Self::MirroredRepeat => {
+                let period = coordinate.rem_euclid(2.0);
+                if period > 1.0 {
+                    2.0 - period
+                } else {
+                    period
+                }
+            }
+            Self::Clamp => coordinate.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Maps a texel index into [0, size).
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn wrap_index(self, index: i32, size: u32) -> u32 {
+        let size = size as i32;
+        let index = match self {
+            Self::Repeat => index.rem_euclid(size),
+            Self::MirroredRepeat => {
+                let period = index.rem_euclid(2 * size);
+                if period >= size {
+                    2 * size - 1 - period
+                } else {
+                    period
+                }
+            }
+            Self::Clamp => index.clamp(0, size - 1),
+        };
+        index as u32
+    }
+}
+
+#[enum_dispatch]
+pub trait Texture: Sync + Send {
+    fn get_properties(&self) -> &TextureProperties;
+
+    /// Value at texture coordinates that were already scaled and offset.
+    fn evaluate(&self, uv: Vec2) -> RGBColor;
+
+    /// Value at the texture coordinates of a hitpoint.
+    fn sample(&self, uv: Vec2) -> RGBColor {
+        self.evaluate(self.get_properties().transform_uv(uv))
+    }
+
+    /// Scalar parameters such as roughness read the red channel, so grayscale maps work as is.
+    fn sample_scalar(&self, uv: Vec2) -> f32 {
+        self.sample(uv).x
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Writes frames to an uncompressed YUV4MPEG2 (`.y4m`) stream, which video encoders such as
+/// ffmpeg read directly. The frames are stored as 8-bit BT.601 limited range YCbCr with the color
+/// planes at half the resolution (4:2:0). Y4M has no field for the color matrix and players assume
+/// BT.601 for it, so no extra flags are needed when encoding.
+pub struct Y4mWriter {
+    writer: BufWriter<File>,
+    width: u32,
+    height: u32,
+}
+
+impl Y4mWriter {
+    pub fn create(
+        path: &Path,
+        width: u32,
+        height: u32,
+        frames_per_second: u32,
+    ) -> Result<Self, Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "YUV4MPEG2 W{width} H{height} F{frames_per_second}:1 Ip A1:1 C420jpeg")?;
+        Ok(Self { writer, width, height })
+    }
+
+    /// Appends one frame of `0x00RRGGBB` pixels, as produced by the renderer.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn write_frame(&mut self, pixel_data: &[u32]) -> Result<(), Error> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        if pixel_data.len() != width * height {
+            return Err(format!(
+                "pixel buffer holds {} pixels, expected {width}x{height}",
+                pixel_data.len()
+            )
+            .into());
+        }
+
+        let colors: Vec<[f32; 3]> = pixel_data.iter().map(|pixel| to_ycbcr(*pixel)).collect();
+        let to_byte = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+
+        let luma: Vec<u8> = colors.iter().map(|color| to_byte(color[0])).collect();
+
+        // Every chroma sample averages a block of 2x2 pixels, odd sizes repeat the last pixel
+        let chroma_width = width.div_ceil(2);
+        let chroma_height = height.div_ceil(2);
+        let mut blue_difference = Vec::with_capacity(chroma_width * chroma_height);
+        let mut red_difference = Vec::with_capacity(chroma_width * chroma_height);
+        for y in 0..chroma_height {
+            for x in 0..chroma_width {
+                let mut sum = [0.0; 2];
+                for (offset_x, offset_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let pixel_x = (x * 2 + offset_x).min(width - 1);
+                    let pixel_y = (y * 2 + offset_y).min(height - 1);
+                    let color = colors[pixel_y * width + pixel_x];
+                    sum[0] += color[1];
+                    sum[1] += color[2];
+                }
+                blue_difference.push(to_byte(sum[0] / 4.0));
+                red_difference.push(to_byte(sum[1] / 4.0));
+            }
+        }
+
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&luma)?;
+        self.writer.write_all(&blue_difference)?;
+        self.writer.write_all(&red_difference)?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Converts a gamma encoded pixel to BT.601 Y, Cb and Cr in the 8-bit studio range, luma from 16
+/// to 235 and chroma from 16 to 240.
+fn to_ycbcr(pixel: u32) -> [f32; 3] {
+    let [b, g, r, _] = pixel.to_le_bytes().map(|channel| f32::from(channel) / 255.0);
+    let luma = 0.114f32.mul_add(b, 0.299f32.mul_add(r, 0.587 * g));
+    [
+        219.0f32.mul_add(luma, 16.0),
+        224.0f32.mul_add((b - luma) / 1.772, 128.0),
+        224.0f32.mul_add((r - luma) / 1.402, 128.0),
+    ]
+}
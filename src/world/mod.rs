@@ -1,4 +1,6 @@
+pub mod scene_file;
 pub mod scene_manager;
+pub mod scene_node;
 pub mod scenegraph;
 #[allow(clippy::module_name_repetitions)]
 pub mod world_creation;
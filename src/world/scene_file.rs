@@ -0,0 +1,1758 @@
+use super::{scene_manager::SceneManager, scene_node::is_valid_scale, scenegraph::Scenegraph};
+use crate::{
+    animation::{track::Animatable, Animation, AnimationTrack, Interpolation, Keyframe, Track},
+    camera::Camera,
+    image_reader::{read_hdr, read_image},
+    lights::{
+        area_light::{AreaLight, AreaLightShape},
+        directional_light::DirectionalLight,
+        environment_light::EnvironmentLight,
+        light_manager::LightManager,
+        light_properties::{LightProperties, LightType},
+        point_light::PointLight,
+        sky_light::SkyLight,
+        spot_light::SpotLight,
+        LightEnum,
+    },
+    materials::{
+        dielectric_material::DielectricMaterial,
+        lambert_material::LambertMaterial,
+        lambert_phong_material::LambertPhongMaterial,
+        material_manager::MaterialManager,
+        material_properties::{Material, MaterialProperties, MaterialTextures},
+        phong_brdf_material::PhongBRDFMaterial,
+        MaterialEnum,
+    },
+    math::rotation_from_euler_degrees,
+    objects::{
+        instance::Instance,
+        object_properties::ObjectProperties,
+        plane::Plane,
+        sphere::Sphere,
+        triangle::{CullMode, Triangle},
+        triangle_mesh::TriangleMesh,
+        ObjectEnum,
+    },
+    projection::{CubeFace, Projection},
+    textures::{
+        CheckerTexture, GradientTexture, GradientType, ImageTexture, NoiseTexture, NoiseType,
+        TextureEnum, TextureFilter, TextureProperties, WrapMode,
+    },
+};
+use glam::{Affine3A, Vec2, Vec3};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use toml::Spanned;
+
+pub type RGBColor = Vec3;
+
+/// Error raised while loading a scene file, pointing at the offending line when it is known.
+#[derive(Debug)]
+pub struct SceneFileError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorDescription {
+    Named(String),
+    Rgb([f32; 3]),
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum CullModeDescription {
+    #[default]
+    None,
+    BackFace,
+    FrontFace,
+}
+
+impl From<CullModeDescription> for CullMode {
+    fn from(cull_mode: CullModeDescription) -> Self {
+        match cull_mode {
+            CullModeDescription::None => Self::None,
+            CullModeDescription::BackFace => Self::BackFace,
+            CullModeDescription::FrontFace => Self::FrontFace,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum WrapModeDescription {
+    #[default]
+    Repeat,
+    MirroredRepeat,
+    Clamp,
+}
+
+impl From<WrapModeDescription> for WrapMode {
+    fn from(wrap_mode: WrapModeDescription) -> Self {
+        match wrap_mode {
+            WrapModeDescription::Repeat => Self::Repeat,
+            WrapModeDescription::MirroredRepeat => Self::MirroredRepeat,
+            WrapModeDescription::Clamp => Self::Clamp,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum TextureFilterDescription {
+    Nearest,
+    #[default]
+    Bilinear,
+}
+
+impl From<TextureFilterDescription> for TextureFilter {
+    fn from(filter: TextureFilterDescription) -> Self {
+        match filter {
+            TextureFilterDescription::Nearest => Self::Nearest,
+            TextureFilterDescription::Bilinear => Self::Bilinear,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum GradientTypeDescription {
+    #[default]
+    Horizontal,
+    Vertical,
+    Radial,
+}
+
+impl From<GradientTypeDescription> for GradientType {
+    fn from(gradient_type: GradientTypeDescription) -> Self {
+        match gradient_type {
+            GradientTypeDescription::Horizontal => Self::Horizontal,
+            GradientTypeDescription::Vertical => Self::Vertical,
+            GradientTypeDescription::Radial => Self::Radial,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum InterpolationDescription {
+    #[default]
+    Linear,
+    Bezier,
+}
+
+impl From<InterpolationDescription> for Interpolation {
+    fn from(interpolation: InterpolationDescription) -> Self {
+        match interpolation {
+            InterpolationDescription::Linear => Self::Linear,
+            InterpolationDescription::Bezier => Self::Bezier,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum ProjectionDescription {
+    #[default]
+    Perspective,
+    Orthographic,
+    Equirectangular,
+    Fisheye,
+    CubePositiveX,
+    CubeNegativeX,
+    CubePositiveY,
+    CubeNegativeY,
+    CubePositiveZ,
+    CubeNegativeZ,
+}
+
+impl From<ProjectionDescription> for Projection {
+    fn from(projection: ProjectionDescription) -> Self {
+        match projection {
+            ProjectionDescription::Perspective => Self::Perspective,
+            ProjectionDescription::Orthographic => Self::Orthographic,
+            ProjectionDescription::Equirectangular => Self::Equirectangular,
+            ProjectionDescription::Fisheye => Self::Fisheye,
+            ProjectionDescription::CubePositiveX => Self::CubeFace(CubeFace::PositiveX),
+            ProjectionDescription::CubeNegativeX => Self::CubeFace(CubeFace::NegativeX),
+            ProjectionDescription::CubePositiveY => Self::CubeFace(CubeFace::PositiveY),
+            ProjectionDescription::CubeNegativeY => Self::CubeFace(CubeFace::NegativeY),
+            ProjectionDescription::CubePositiveZ => Self::CubeFace(CubeFace::PositiveZ),
+            ProjectionDescription::CubeNegativeZ => Self::CubeFace(CubeFace::NegativeZ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDescription {
+    #[serde(default = "default_camera_position")]
+    position: [f32; 3],
+    #[serde(default = "default_camera_fov")]
+    fov: f32,
+    target: Option<[f32; 3]>,
+    #[serde(default)]
+    aperture_radius: f32,
+    /// Defaults to the distance to the target
+    focal_distance: Option<f32>,
+    #[serde(default)]
+    autofocus: bool,
+    #[serde(default)]
+    projection: ProjectionDescription,
+    /// Defaults to the height the perspective projection shows at the focal distance
+    orthographic_height: Option<f32>,
+    fisheye_fov: Option<f32>,
+}
+
+/// Texture coordinates are multiplied by `scale` and moved by `offset` before the lookup
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDescription {
+    /// PNG, JPEG or Radiance `.hdr` image
+    Image {
+        name: String,
+        file: PathBuf,
+        /// Defaults to true for PNG and JPEG, set it to false for data such as roughness maps
+        srgb: Option<bool>,
+        #[serde(default)]
+        wrap: WrapModeDescription,
+        #[serde(default)]
+        filter: TextureFilterDescription,
+        #[serde(default = "default_texture_scale")]
+        scale: [f32; 2],
+        #[serde(default)]
+        offset: [f32; 2],
+    },
+    Checker {
+        name: String,
+        #[serde(default = "default_white_color")]
+        even: ColorDescription,
+        #[serde(default = "default_black_color")]
+        odd: ColorDescription,
+        #[serde(default = "default_texture_scale")]
+        scale: [f32; 2],
+        #[serde(default)]
+        offset: [f32; 2],
+    },
+    Perlin {
+        name: String,
+        #[serde(default = "default_black_color")]
+        low: ColorDescription,
+        #[serde(default = "default_white_color")]
+        high: ColorDescription,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default = "default_texture_scale")]
+        scale: [f32; 2],
+        #[serde(default)]
+        offset: [f32; 2],
+    },
+    Worley {
+        name: String,
+        #[serde(default = "default_black_color")]
+        low: ColorDescription,
+        #[serde(default = "default_white_color")]
+        high: ColorDescription,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default = "default_texture_scale")]
+        scale: [f32; 2],
+        #[serde(default)]
+        offset: [f32; 2],
+    },
+    Gradient {
+        name: String,
+        #[serde(default = "default_black_color")]
+        start: ColorDescription,
+        #[serde(default = "default_white_color")]
+        end: ColorDescription,
+        #[serde(default)]
+        direction: GradientTypeDescription,
+        #[serde(default = "default_gradient_wrap")]
+        wrap: WrapModeDescription,
+        #[serde(default = "default_texture_scale")]
+        scale: [f32; 2],
+        #[serde(default)]
+        offset: [f32; 2],
+    },
+}
+
+/// The `*_texture` fields name a texture that is multiplied with the constant parameter
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambert {
+        name: String,
+        #[serde(default = "default_white_color")]
+        color: ColorDescription,
+        #[serde(default)]
+        reflectiveness: f32,
+        albedo_texture: Option<String>,
+        reflectiveness_texture: Option<String>,
+        normal_texture: Option<String>,
+        bump_texture: Option<String>,
+        #[serde(default = "default_one")]
+        normal_strength: f32,
+    },
+    LambertPhong {
+        name: String,
+        #[serde(default = "default_white_color")]
+        color: ColorDescription,
+        #[serde(default)]
+        reflectiveness: f32,
+        #[serde(default = "default_one")]
+        specular_reflectance: f32,
+        #[serde(default = "default_phong_exponent")]
+        phong_exponent: i32,
+        albedo_texture: Option<String>,
+        reflectiveness_texture: Option<String>,
+        normal_texture: Option<String>,
+        bump_texture: Option<String>,
+        #[serde(default = "default_one")]
+        normal_strength: f32,
+    },
+    PhongBrdf {
+        name: String,
+        #[serde(default = "default_white_color")]
+        albedo: ColorDescription,
+        roughness: f32,
+        #[serde(default)]
+        metal: bool,
+        #[serde(default = "default_one")]
+        reflectiveness: f32,
+        albedo_texture: Option<String>,
+        roughness_texture: Option<String>,
+        /// Replaces `metal`, 1 is metal and 0 is not
+        metalness_texture: Option<String>,
+        reflectiveness_texture: Option<String>,
+        normal_texture: Option<String>,
+        bump_texture: Option<String>,
+        #[serde(default = "default_one")]
+        normal_strength: f32,
+    },
+    Dielectric {
+        name: String,
+        index_of_refraction: f32,
+        #[serde(default = "default_white_color")]
+        tint: ColorDescription,
+        #[serde(default = "default_absorption_density")]
+        absorption_density: f32,
+        normal_texture: Option<String>,
+        bump_texture: Option<String>,
+        #[serde(default = "default_one")]
+        normal_strength: f32,
+    },
+}
+
+impl MaterialDescription {
+    /// Names of the albedo, roughness, metalness, reflectiveness, normal and bump textures.
+    const fn get_texture_names(&self) -> [Option<&String>; 6] {
+        match self {
+            Self::Lambert {
+                albedo_texture,
+                reflectiveness_texture,
+                normal_texture,
+                bump_texture,
+                ..
+            }
+            | Self::LambertPhong {
+                albedo_texture,
+                reflectiveness_texture,
+                normal_texture,
+                bump_texture,
+                ..
+            } => [
+                albedo_texture.as_ref(),
+                None,
+                None,
+                reflectiveness_texture.as_ref(),
+                normal_texture.as_ref(),
+                bump_texture.as_ref(),
+            ],
+            Self::PhongBrdf {
+                albedo_texture,
+                roughness_texture,
+                metalness_texture,
+                reflectiveness_texture,
+                normal_texture,
+                bump_texture,
+                ..
+            } => [
+                albedo_texture.as_ref(),
+                roughness_texture.as_ref(),
+                metalness_texture.as_ref(),
+                reflectiveness_texture.as_ref(),
+                normal_texture.as_ref(),
+                bump_texture.as_ref(),
+            ],
+            Self::Dielectric { normal_texture, bump_texture, .. } => {
+                [None, None, None, None, normal_texture.as_ref(), bump_texture.as_ref()]
+            }
+        }
+    }
+
+    const fn get_normal_strength(&self) -> f32 {
+        match self {
+            Self::Lambert { normal_strength, .. }
+            | Self::LambertPhong { normal_strength, .. }
+            | Self::PhongBrdf { normal_strength, .. }
+            | Self::Dielectric { normal_strength, .. } => *normal_strength,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum LightDescription {
+    Point {
+        position: [f32; 3],
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+    },
+    Directional {
+        direction: [f32; 3],
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+    },
+    /// Cone angles are measured from `direction`, in degrees
+    Spot {
+        position: [f32; 3],
+        direction: [f32; 3],
+        #[serde(default = "default_inner_cone_angle")]
+        inner_angle: f32,
+        #[serde(default = "default_outer_cone_angle")]
+        outer_angle: f32,
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+    },
+    /// Equirectangular Radiance `.hdr` image surrounding the scene, rotated by Euler angles in degrees
+    Environment {
+        file: PathBuf,
+        #[serde(default)]
+        rotation: [f32; 3],
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        #[serde(default = "default_one")]
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default = "default_samples_per_axis")]
+        samples_per_axis: u32,
+    },
+    /// Analytic sky with a matching sun, angles in degrees
+    Sky {
+        #[serde(default = "default_sun_elevation")]
+        sun_elevation: f32,
+        #[serde(default)]
+        sun_azimuth: f32,
+        #[serde(default = "default_turbidity")]
+        turbidity: f32,
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        #[serde(default = "default_one")]
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default = "default_samples_per_axis")]
+        samples_per_axis: u32,
+    },
+    /// Emits towards `edge_u × edge_v`
+    Rectangle {
+        position: [f32; 3],
+        edge_u: [f32; 3],
+        edge_v: [f32; 3],
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default = "default_samples_per_axis")]
+        samples_per_axis: u32,
+    },
+    Disk {
+        position: [f32; 3],
+        normal: [f32; 3],
+        radius: f32,
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default = "default_samples_per_axis")]
+        samples_per_axis: u32,
+    },
+    Sphere {
+        position: [f32; 3],
+        radius: f32,
+        #[serde(default = "default_white")]
+        color: [f32; 3],
+        intensity: f32,
+        #[serde(default = "default_true")]
+        enabled: bool,
+        #[serde(default = "default_samples_per_axis")]
+        samples_per_axis: u32,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDescription {
+    Sphere {
+        /// Moves the object along with a node of the hierarchy
+        node: Option<String>,
+        material: String,
+        position: [f32; 3],
+        radius: f32,
+    },
+    Plane {
+        node: Option<String>,
+        material: String,
+        position: [f32; 3],
+        normal: [f32; 3],
+    },
+    Triangle {
+        node: Option<String>,
+        material: String,
+        #[serde(default)]
+        position: [f32; 3],
+        vertices: [[f32; 3]; 3],
+        #[serde(default)]
+        cull_mode: CullModeDescription,
+    },
+    Mesh {
+        node: Option<String>,
+        material: String,
+        #[serde(default)]
+        position: [f32; 3],
+        obj: PathBuf,
+        /// Scene materials for the usemtl names of the OBJ file, other faces use `material`
+        #[serde(default)]
+        materials: HashMap<String, String>,
+        #[serde(default)]
+        cull_mode: CullModeDescription,
+        /// Generates smooth normals for faces without OBJ normals, in degrees
+        crease_angle: Option<f32>,
+    },
+    Instance {
+        node: Option<String>,
+        mesh: String,
+        /// Overrides the material of the mesh
+        material: Option<String>,
+        #[serde(default)]
+        position: [f32; 3],
+        /// Degrees around the x, then the y and then the z axis
+        #[serde(default)]
+        rotation: [f32; 3],
+        #[serde(default)]
+        scale: ScaleDescription,
+    },
+}
+
+impl ObjectDescription {
+    const fn get_node(&self) -> Option<&String> {
+        match self {
+            Self::Sphere { node, .. }
+            | Self::Plane { node, .. }
+            | Self::Triangle { node, .. }
+            | Self::Mesh { node, .. }
+            | Self::Instance { node, .. } => node.as_ref(),
+        }
+    }
+}
+
+/// Named group of objects, placed relative to its parent node
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeDescription {
+    name: String,
+    /// Has to come before the node in the file
+    parent: Option<String>,
+    #[serde(default)]
+    position: [f32; 3],
+    /// Degrees around the x, then the y and then the z axis
+    #[serde(default)]
+    rotation: [f32; 3],
+    #[serde(default)]
+    scale: ScaleDescription,
+}
+
+/// A mesh that is only loaded once and placed in the scene by `instance` objects
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MeshDescription {
+    name: String,
+    material: String,
+    obj: PathBuf,
+    #[serde(default)]
+    materials: HashMap<String, String>,
+    #[serde(default)]
+    cull_mode: CullModeDescription,
+    crease_angle: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScaleDescription {
+    Uniform(f32),
+    PerAxis([f32; 3]),
+}
+
+impl Default for ScaleDescription {
+    fn default() -> Self {
+        Self::Uniform(1.0)
+    }
+}
+
+impl ScaleDescription {
+    fn get_vector(&self) -> Result<Vec3, String> {
+        match self {
+            Self::Uniform(scale) => check_scale(Vec3::splat(*scale)),
+            Self::PerAxis(scale) => check_scale(Vec3::from_array(*scale)),
+        }
+    }
+}
+
+/// Playback of the `[[tracks]]`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnimationDescription {
+    /// Defaults to the time of the last keyframe
+    duration: Option<f32>,
+    #[serde(rename = "loop", default = "default_true")]
+    is_looping: bool,
+    #[serde(default = "default_true")]
+    autoplay: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyValueDescription {
+    Scalar(f32),
+    Vector([f32; 3]),
+}
+
+impl KeyValueDescription {
+    fn get_scalar(&self) -> Result<f32, String> {
+        match self {
+            Self::Scalar(value) => Ok(*value),
+            Self::Vector(_) => Err("expected a single number as the value of a key".to_string()),
+        }
+    }
+
+    fn get_vector(&self) -> Result<Vec3, String> {
+        match self {
+            Self::Scalar(_) => Err("expected three numbers as the value of a key".to_string()),
+            Self::Vector(value) => Ok(Vec3::from_array(*value)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyframeDescription {
+    time: f32,
+    value: KeyValueDescription,
+    /// Overrides the interpolation of the track towards the next key
+    interpolation: Option<InterpolationDescription>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum NodePropertyDescription {
+    Position,
+    Rotation,
+    Scale,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum LightPropertyDescription {
+    Color,
+    Intensity,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum CameraPropertyDescription {
+    Position,
+    Target,
+    Fov,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TrackDescription {
+    Node {
+        node: String,
+        property: NodePropertyDescription,
+        #[serde(default)]
+        interpolation: InterpolationDescription,
+        keys: Vec<KeyframeDescription>,
+    },
+    Light {
+        /// Index of the light in `[[lights]]`, starting at 0
+        light: usize,
+        property: LightPropertyDescription,
+        #[serde(default)]
+        interpolation: InterpolationDescription,
+        keys: Vec<KeyframeDescription>,
+    },
+    Camera {
+        property: CameraPropertyDescription,
+        #[serde(default)]
+        interpolation: InterpolationDescription,
+        keys: Vec<KeyframeDescription>,
+    },
+}
+
+// The tagged descriptions lose track of where they are in the file once deserialized, so every
+// entry is first read as a plain table with its position and only then turned into a description.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSceneFile {
+    camera: Option<CameraDescription>,
+    animation: Option<AnimationDescription>,
+    #[serde(default)]
+    textures: Vec<Spanned<toml::Table>>,
+    #[serde(default)]
+    materials: Vec<Spanned<toml::Table>>,
+    #[serde(default)]
+    lights: Vec<Spanned<toml::Table>>,
+    #[serde(default)]
+    meshes: Vec<Spanned<toml::Table>>,
+    #[serde(default)]
+    nodes: Vec<Spanned<toml::Table>>,
+    #[serde(default)]
+    objects: Vec<Spanned<toml::Table>>,
+    #[serde(default)]
+    tracks: Vec<Spanned<toml::Table>>,
+}
+
+struct SceneEntry<T> {
+    line: usize,
+    description: T,
+}
+
+struct SceneFile {
+    camera: Option<CameraDescription>,
+    animation: Option<AnimationDescription>,
+    textures: Vec<SceneEntry<TextureDescription>>,
+    materials: Vec<SceneEntry<MaterialDescription>>,
+    lights: Vec<SceneEntry<LightDescription>>,
+    meshes: Vec<SceneEntry<MeshDescription>>,
+    nodes: Vec<SceneEntry<NodeDescription>>,
+    objects: Vec<SceneEntry<ObjectDescription>>,
+    tracks: Vec<SceneEntry<TrackDescription>>,
+}
+
+const fn default_camera_position() -> [f32; 3] {
+    [0.0, 2.0, 15.0]
+}
+
+const fn default_camera_fov() -> f32 {
+    45.0
+}
+
+const fn default_inner_cone_angle() -> f32 {
+    20.0
+}
+
+const fn default_outer_cone_angle() -> f32 {
+    30.0
+}
+
+const fn default_sun_elevation() -> f32 {
+    45.0
+}
+
+const fn default_turbidity() -> f32 {
+    3.0
+}
+
+const fn default_one() -> f32 {
+    1.0
+}
+
+const fn default_phong_exponent() -> i32 {
+    60
+}
+
+fn default_white_color() -> ColorDescription {
+    ColorDescription::Named(String::from("White"))
+}
+
+fn default_black_color() -> ColorDescription {
+    ColorDescription::Named(String::from("Black"))
+}
+
+const fn default_texture_scale() -> [f32; 2] {
+    [1.0, 1.0]
+}
+
+const fn default_octaves() -> u32 {
+    4
+}
+
+const fn default_gradient_wrap() -> WrapModeDescription {
+    WrapModeDescription::Clamp
+}
+
+const fn default_absorption_density() -> f32 {
+    0.5
+}
+
+const fn default_white() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+const fn default_samples_per_axis() -> u32 {
+    4
+}
+
+/// A scene loaded from a TOML file, describing the camera, named textures and materials, lights
+/// and objects. Materials have to be created before the scene so objects can refer to them by name.
+///
+/// ```toml
+/// [camera]
+/// position = [0.0, 2.0, 15.0]
+/// fov = 45.0
+///
+/// [[textures]]
+/// type = "checker"
+/// name = "tiles"
+/// scale = [0.5, 0.5]
+///
+/// [[materials]]
+/// type = "lambert"
+/// name = "floor"
+/// color = "Grey"
+/// albedo_texture = "tiles"
+///
+/// [[lights]]
+/// type = "point"
+/// position = [0.0, 5.0, 5.0]
+/// intensity = 50.0
+///
+/// [[objects]]
+/// type = "plane"
+/// material = "floor"
+/// position = [0.0, 0.0, 0.0]
+/// normal = [0.0, 1.0, 0.0]
+/// ```
+pub struct SceneDescription {
+    path: PathBuf,
+    scene_file: SceneFile,
+}
+
+impl SceneDescription {
+    pub fn from_file(path: &Path) -> Result<Self, SceneFileError> {
+        let source = std::fs::read_to_string(path).map_err(|error| SceneFileError {
+            path: path.to_path_buf(),
+            line: None,
+            message: error.to_string(),
+        })?;
+
+        Self::from_source(path, &source)
+    }
+
+    pub fn from_source(path: &Path, source: &str) -> Result<Self, SceneFileError> {
+        let raw_scene_file: RawSceneFile =
+            toml::from_str(source).map_err(|error: toml::de::Error| SceneFileError {
+                path: path.to_path_buf(),
+                line: error.span().map(|span| get_line_number(source, span.start)),
+                message: error.message().to_string(),
+            })?;
+
+        let scene_file = SceneFile {
+            camera: raw_scene_file.camera,
+            animation: raw_scene_file.animation,
+            textures: Self::parse_entries(path, source, raw_scene_file.textures)?,
+            materials: Self::parse_entries(path, source, raw_scene_file.materials)?,
+            lights: Self::parse_entries(path, source, raw_scene_file.lights)?,
+            meshes: Self::parse_entries(path, source, raw_scene_file.meshes)?,
+            nodes: Self::parse_entries(path, source, raw_scene_file.nodes)?,
+            objects: Self::parse_entries(path, source, raw_scene_file.objects)?,
+            tracks: Self::parse_entries(path, source, raw_scene_file.tracks)?,
+        };
+
+        Ok(Self { path: path.to_path_buf(), scene_file })
+    }
+
+    fn parse_entries<T: DeserializeOwned>(
+        path: &Path,
+        source: &str,
+        entries: Vec<Spanned<toml::Table>>,
+    ) -> Result<Vec<SceneEntry<T>>, SceneFileError> {
+        entries
+            .into_iter()
+            .map(|entry| {
+                let line = get_line_number(source, entry.span().start);
+                toml::Value::Table(entry.into_inner())
+                    .try_into()
+                    .map(|description| SceneEntry { line, description })
+                    .map_err(|error: toml::de::Error| SceneFileError {
+                        path: path.to_path_buf(),
+                        line: Some(line),
+                        message: error.message().to_string(),
+                    })
+            })
+            .collect()
+    }
+
+    pub fn create_camera(&self) -> Camera {
+        let Some(description) = &self.scene_file.camera else {
+            return Camera::new(Vec3::from_array(default_camera_position()), default_camera_fov());
+        };
+
+        let position = Vec3::from_array(description.position);
+        let mut camera = Camera::new(position, description.fov);
+        if let Some(target) = description.target {
+            camera.set_target(Vec3::from_array(target));
+        }
+        camera.set_aperture_radius(description.aperture_radius);
+        let target_distance =
+            description.target.map(|target| position.distance(Vec3::from_array(target)));
+        if let Some(focal_distance) = description.focal_distance.or(target_distance) {
+            camera.set_focal_distance(focal_distance);
+        }
+        camera.set_autofocus(description.autofocus);
+        camera.set_projection(description.projection.into());
+        if let Some(orthographic_height) = description.orthographic_height {
+            camera.set_orthographic_height(orthographic_height);
+        }
+        if let Some(fisheye_fov) = description.fisheye_fov {
+            camera.set_fisheye_fov(fisheye_fov);
+        }
+        camera.update_look_at();
+        camera
+    }
+
+    /// Also creates the textures, which the materials refer to by name.
+    pub fn create_materials(
+        &self,
+        material_manager: &mut MaterialManager<'_>,
+    ) -> Result<(), SceneFileError> {
+        self.create_textures(material_manager)?;
+
+        for entry in &self.scene_file.materials {
+            let error = |message: String| self.error_at(entry, message);
+
+            let (name, mut material) = match &entry.description {
+                MaterialDescription::Lambert { name, color, reflectiveness, .. } => {
+                    let color = Self::resolve_color(material_manager, color).map_err(error)?;
+                    (name, MaterialEnum::Lambert(LambertMaterial::new(color, 1.0, *reflectiveness)))
+                }
+                MaterialDescription::LambertPhong {
+                    name,
+                    color,
+                    reflectiveness,
+                    specular_reflectance,
+                    phong_exponent,
+                    ..
+                } => {
+                    let color = Self::resolve_color(material_manager, color).map_err(error)?;
+                    (
+                        name,
+                        MaterialEnum::LambertPhong(LambertPhongMaterial::new(
+                            MaterialProperties::new(color, 1.0, *reflectiveness),
+                            *specular_reflectance,
+                            *phong_exponent,
+                        )),
+                    )
+                }
+                MaterialDescription::PhongBrdf {
+                    name,
+                    albedo,
+                    roughness,
+                    metal,
+                    reflectiveness,
+                    ..
+                } => {
+                    // Metals are looked up in the table of base reflectivities first
+                    let albedo = match albedo {
+                        ColorDescription::Named(metal_name) if *metal => material_manager
+                            .get_linear_fresnel(metal_name)
+                            .ok_or_else(|| format!("unknown metal '{metal_name}'"))
+                            .map_err(error)?,
+                        _ => Self::resolve_color(material_manager, albedo).map_err(error)?,
+                    };
+                    (
+                        name,
+                        MaterialEnum::PhongBRDFMaterial(PhongBRDFMaterial::new(
+                            albedo,
+                            *roughness,
+                            *metal,
+                            1.0,
+                            *reflectiveness,
+                        )),
+                    )
+                }
+                MaterialDescription::Dielectric {
+                    name,
+                    index_of_refraction,
+                    tint,
+                    absorption_density,
+                    ..
+                } => {
+                    if *index_of_refraction < 1.0 {
+                        return Err(error(String::from("index_of_refraction must be at least 1")));
+                    }
+                    let tint = Self::resolve_color(material_manager, tint).map_err(error)?;
+                    (
+                        name,
+                        MaterialEnum::Dielectric(DielectricMaterial::new(
+                            MaterialProperties::new(tint, 1.0, 1.0),
+                            *index_of_refraction,
+                            (RGBColor::ONE - tint) * *absorption_density,
+                            1.0,
+                            default_phong_exponent(),
+                        )),
+                    )
+                }
+            };
+
+            if material_manager.get_material(name).is_some() {
+                return Err(error(format!("material '{name}' is defined more than once")));
+            }
+            material.set_textures(
+                Self::resolve_textures(material_manager, &entry.description).map_err(error)?,
+            );
+            material_manager.add_material(name.clone(), material);
+        }
+
+        Ok(())
+    }
+
+    fn create_textures(
+        &self,
+        material_manager: &mut MaterialManager<'_>,
+    ) -> Result<(), SceneFileError> {
+        for entry in &self.scene_file.textures {
+            let error = |message: String| self.error_at(entry, message);
+            let properties = |scale: [f32; 2], offset: [f32; 2]| {
+                TextureProperties::new(Vec2::from_array(scale), Vec2::from_array(offset))
+            };
+
+            let (name, texture) = match &entry.description {
+                TextureDescription::Image { name, file, srgb, wrap, filter, scale, offset } => {
+                    let image = read_image(&self.resolve_path(file)).map_err(|image_error| {
+                        error(format!("failed to load texture: {image_error}"))
+                    })?;
+                    // Radiance images always hold linear values
+                    let is_hdr = file.extension().is_some_and(|extension| extension == "hdr");
+                    (
+                        name,
+                        TextureEnum::Image(ImageTexture::new(
+                            properties(*scale, *offset),
+                            image,
+                            srgb.unwrap_or(!is_hdr),
+                            (*wrap).into(),
+                            (*filter).into(),
+                        )),
+                    )
+                }
+                TextureDescription::Checker { name, even, odd, scale, offset } => (
+                    name,
+                    TextureEnum::Checker(CheckerTexture::new(
+                        properties(*scale, *offset),
+                        Self::resolve_color(material_manager, even).map_err(error)?,
+                        Self::resolve_color(material_manager, odd).map_err(error)?,
+                    )),
+                ),
+                TextureDescription::Perlin { name, low, high, octaves, seed, scale, offset }
+                | TextureDescription::Worley { name, low, high, octaves, seed, scale, offset } => {
+                    let noise_type =
+                        if matches!(entry.description, TextureDescription::Perlin { .. }) {
+                            NoiseType::Perlin
+                        } else {
+                            NoiseType::Worley
+                        };
+                    (
+                        name,
+                        TextureEnum::Noise(NoiseTexture::new(
+                            properties(*scale, *offset),
+                            noise_type,
+                            Self::resolve_color(material_manager, low).map_err(error)?,
+                            Self::resolve_color(material_manager, high).map_err(error)?,
+                            *octaves,
+                            *seed,
+                        )),
+                    )
+                }
+                TextureDescription::Gradient {
+                    name,
+                    start,
+                    end,
+                    direction,
+                    wrap,
+                    scale,
+                    offset,
+                } => (
+                    name,
+                    TextureEnum::Gradient(GradientTexture::new(
+                        properties(*scale, *offset),
+                        (*direction).into(),
+                        Self::resolve_color(material_manager, start).map_err(error)?,
+                        Self::resolve_color(material_manager, end).map_err(error)?,
+                        (*wrap).into(),
+                    )),
+                ),
+            };
+
+            if material_manager.get_texture(name).is_some() {
+                return Err(error(format!("texture '{name}' is defined more than once")));
+            }
+            material_manager.add_texture(name.clone(), texture);
+        }
+
+        Ok(())
+    }
+
+    fn resolve_textures(
+        material_manager: &MaterialManager<'_>,
+        description: &MaterialDescription,
+    ) -> Result<MaterialTextures, String> {
+        let [albedo, roughness, metalness, reflectiveness, normal, bump] =
+            description.get_texture_names().map(|name| {
+                name.map(|name| {
+                    material_manager
+                        .get_texture(name)
+                        .ok_or_else(|| format!("unknown texture '{name}'"))
+                })
+                .transpose()
+            });
+        Ok(MaterialTextures {
+            albedo: albedo?,
+            roughness: roughness?,
+            metalness: metalness?,
+            reflectiveness: reflectiveness?,
+            normal: normal?,
+            bump: bump?,
+            normal_strength: description.get_normal_strength(),
+        })
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn create_lights(
+        &self,
+        light_manager: &mut LightManager<LightEnum>,
+    ) -> Result<(), SceneFileError> {
+        for entry in &self.scene_file.lights {
+            let light = match entry.description {
+                LightDescription::Point { position, color, intensity, enabled } => {
+                    LightEnum::Point(PointLight::new(
+                        LightProperties::new(
+                            Vec3::from_array(color),
+                            intensity,
+                            enabled,
+                            LightType::Point,
+                        ),
+                        Vec3::from_array(position),
+                    ))
+                }
+                LightDescription::Directional { direction, color, intensity, enabled } => {
+                    let direction = Vec3::from_array(direction);
+                    if direction.length_squared() == 0.0 {
+                        return Err(
+                            self.error_at(entry, String::from("direction must not be zero"))
+                        );
+                    }
+                    LightEnum::Directional(DirectionalLight::new(
+                        LightProperties::new(
+                            Vec3::from_array(color),
+                            intensity,
+                            enabled,
+                            LightType::Directional,
+                        ),
+                        direction.normalize(),
+                    ))
+                }
+                LightDescription::Spot {
+                    position,
+                    direction,
+                    inner_angle,
+                    outer_angle,
+                    color,
+                    intensity,
+                    enabled,
+                } => {
+                    let direction = Vec3::from_array(direction);
+                    if direction.length_squared() == 0.0 {
+                        return Err(
+                            self.error_at(entry, String::from("direction must not be zero"))
+                        );
+                    }
+                    if !(0.0..=90.0).contains(&outer_angle) {
+                        return Err(self.error_at(
+                            entry,
+                            String::from("outer_angle must be between 0 and 90 degrees"),
+                        ));
+                    }
+                    if !(0.0..=outer_angle).contains(&inner_angle) {
+                        return Err(self.error_at(
+                            entry,
+                            String::from("inner_angle must be between 0 and outer_angle"),
+                        ));
+                    }
+                    LightEnum::Spot(SpotLight::new(
+                        LightProperties::new(
+                            Vec3::from_array(color),
+                            intensity,
+                            enabled,
+                            LightType::Spot,
+                        ),
+                        Vec3::from_array(position),
+                        direction,
+                        inner_angle,
+                        outer_angle,
+                    ))
+                }
+                LightDescription::Environment {
+                    ref file,
+                    rotation,
+                    color,
+                    intensity,
+                    enabled,
+                    samples_per_axis,
+                } => {
+                    let image = read_hdr(&self.resolve_path(file)).map_err(|hdr_error| {
+                        self.error_at(entry, format!("failed to load environment map: {hdr_error}"))
+                    })?;
+                    LightEnum::Environment(EnvironmentLight::new(
+                        LightProperties::new(
+                            Vec3::from_array(color),
+                            intensity,
+                            enabled,
+                            LightType::Environment,
+                        ),
+                        image,
+                        Vec3::from_array(rotation.map(f32::to_radians)),
+                        samples_per_axis,
+                    ))
+                }
+                LightDescription::Sky {
+                    sun_elevation,
+                    sun_azimuth,
+                    turbidity,
+                    color,
+                    intensity,
+                    enabled,
+                    samples_per_axis,
+                } => {
+                    if !(SkyLight::MIN_TURBIDITY..=SkyLight::MAX_TURBIDITY).contains(&turbidity) {
+                        return Err(self.error_at(
+                            entry,
+                            format!(
+                                "turbidity must be between {} and {}",
+                                SkyLight::MIN_TURBIDITY,
+                                SkyLight::MAX_TURBIDITY
+                            ),
+                        ));
+                    }
+                    LightEnum::Sky(SkyLight::new(
+                        LightProperties::new(
+                            Vec3::from_array(color),
+                            intensity,
+                            enabled,
+                            LightType::Sky,
+                        ),
+                        sun_elevation,
+                        sun_azimuth,
+                        turbidity,
+                        samples_per_axis,
+                    ))
+                }
+                LightDescription::Rectangle {
+                    position,
+                    edge_u,
+                    edge_v,
+                    color,
+                    intensity,
+                    enabled,
+                    samples_per_axis,
+                } => {
+                    let (edge_u, edge_v) = (Vec3::from_array(edge_u), Vec3::from_array(edge_v));
+                    if edge_u.cross(edge_v).length_squared() == 0.0 {
+                        return Err(self.error_at(
+                            entry,
+                            String::from("edge_u and edge_v must span a rectangle"),
+                        ));
+                    }
+                    Self::create_area_light(
+                        position,
+                        AreaLightShape::Rectangle { edge_u, edge_v },
+                        color,
+                        intensity,
+                        enabled,
+                        samples_per_axis,
+                    )
+                }
+                LightDescription::Disk {
+                    position,
+                    normal,
+                    radius,
+                    color,
+                    intensity,
+                    enabled,
+                    samples_per_axis,
+                } => {
+                    let normal = Vec3::from_array(normal);
+                    if normal.length_squared() == 0.0 || radius <= 0.0 {
+                        return Err(self.error_at(
+                            entry,
+                            String::from("disk needs a non-zero normal and a positive radius"),
+                        ));
+                    }
+                    Self::create_area_light(
+                        position,
+                        AreaLightShape::Disk { normal, radius },
+                        color,
+                        intensity,
+                        enabled,
+                        samples_per_axis,
+                    )
+                }
+                LightDescription::Sphere {
+                    position,
+                    radius,
+                    color,
+                    intensity,
+                    enabled,
+                    samples_per_axis,
+                } => {
+                    if radius <= 0.0 {
+                        return Err(
+                            self.error_at(entry, String::from("radius must be greater than 0"))
+                        );
+                    }
+                    Self::create_area_light(
+                        position,
+                        AreaLightShape::Sphere { radius },
+                        color,
+                        intensity,
+                        enabled,
+                        samples_per_axis,
+                    )
+                }
+            };
+
+            light_manager.add_light(light);
+        }
+
+        Ok(())
+    }
+
+    fn create_area_light(
+        position: [f32; 3],
+        shape: AreaLightShape,
+        color: [f32; 3],
+        intensity: f32,
+        enabled: bool,
+        samples_per_axis: u32,
+    ) -> LightEnum {
+        LightEnum::Area(AreaLight::new(
+            LightProperties::new(Vec3::from_array(color), intensity, enabled, LightType::Area),
+            Vec3::from_array(position),
+            shape,
+            samples_per_axis,
+        ))
+    }
+
+    pub fn create_scene<'a>(
+        &self,
+        scene_manager: &mut SceneManager<'a>,
+        material_manager: &'a MaterialManager<'a>,
+    ) -> Result<(), SceneFileError> {
+        let mut scene = Scenegraph::new();
+        let meshes = self.create_meshes(material_manager)?;
+        self.create_nodes(&mut scene)?;
+
+        for entry in &self.scene_file.objects {
+            let error = |message: String| self.error_at(entry, message);
+            let get_material = |name: &str| {
+                material_manager
+                    .get_material(name)
+                    .ok_or_else(|| error(format!("unknown material '{name}'")))
+            };
+
+            let object = match &entry.description {
+                ObjectDescription::Sphere { material, position, radius, .. } => {
+                    if *radius <= 0.0 {
+                        return Err(error(String::from("radius must be greater than 0")));
+                    }
+                    ObjectEnum::Sphere(Sphere::new(
+                        ObjectProperties::new(Vec3::from_array(*position), get_material(material)?),
+                        *radius,
+                    ))
+                }
+                ObjectDescription::Plane { material, position, normal, .. } => {
+                    if Vec3::from_array(*normal).length_squared() == 0.0 {
+                        return Err(error(String::from("normal must not be zero")));
+                    }
+                    ObjectEnum::Plane(Plane::new(
+                        ObjectProperties::new(Vec3::from_array(*position), get_material(material)?),
+                        Vec3::from_array(*normal),
+                    ))
+                }
+                ObjectDescription::Triangle { material, position, vertices, cull_mode, .. } => {
+                    ObjectEnum::Triangle(Triangle::new(
+                        ObjectProperties::new(Vec3::from_array(*position), get_material(material)?),
+                        vertices.map(Vec3::from_array),
+                        (*cull_mode).into(),
+                    ))
+                }
+                ObjectDescription::Mesh {
+                    material,
+                    position,
+                    obj,
+                    materials,
+                    cull_mode,
+                    crease_angle,
+                    ..
+                } => ObjectEnum::TriangleMesh(
+                    self.load_mesh(
+                        ObjectProperties::new(Vec3::from_array(*position), get_material(material)?),
+                        obj,
+                        material_manager,
+                        materials,
+                        *cull_mode,
+                        *crease_angle,
+                    )
+                    .map_err(error)?,
+                ),
+                ObjectDescription::Instance {
+                    mesh, material, position, rotation, scale, ..
+                } => {
+                    let geometry = meshes
+                        .get(mesh.as_str())
+                        .ok_or_else(|| error(format!("unknown mesh '{mesh}'")))?;
+                    let transform =
+                        Self::create_transform(*position, *rotation, scale).map_err(error)?;
+                    let material = material.as_deref().map(get_material).transpose()?;
+                    ObjectEnum::Instance(Instance::new(Arc::clone(geometry), transform, material))
+                }
+            };
+
+            match entry.description.get_node() {
+                Some(node) => {
+                    let node = scene
+                        .find_node(node)
+                        .ok_or_else(|| error(format!("unknown node '{node}'")))?;
+                    scene.add_object_to_node(node, object);
+                }
+                None => scene.add_object(object),
+            }
+        }
+
+        let animation = self.create_animation(&scene)?;
+        scene.set_animation(animation);
+        scene_manager.add_scene(scene);
+        Ok(())
+    }
+
+    /// Loads the `[[meshes]]` that instances refer to by name.
+    fn create_meshes<'a>(
+        &self,
+        material_manager: &'a MaterialManager<'a>,
+    ) -> Result<HashMap<&str, Arc<ObjectEnum<'a>>>, SceneFileError> {
+        let mut meshes = HashMap::new();
+
+        for entry in &self.scene_file.meshes {
+            let description = &entry.description;
+            if meshes.contains_key(description.name.as_str()) {
+                return Err(self.error_at(
+                    entry,
+                    format!("mesh '{}' is defined more than once", description.name),
+                ));
+            }
+            let material =
+                material_manager.get_material(&description.material).ok_or_else(|| {
+                    self.error_at(entry, format!("unknown material '{}'", description.material))
+                })?;
+            let triangle_mesh = self
+                .load_mesh(
+                    ObjectProperties::new(Vec3::ZERO, material),
+                    &description.obj,
+                    material_manager,
+                    &description.materials,
+                    description.cull_mode,
+                    description.crease_angle,
+                )
+                .map_err(|message| self.error_at(entry, message))?;
+            meshes.insert(description.name.as_str(), Arc::new(ObjectEnum::from(triangle_mesh)));
+        }
+
+        Ok(meshes)
+    }
+
+    /// Builds the node hierarchy, every parent has to be listed before its children.
+    fn create_nodes(&self, scene: &mut Scenegraph<'_>) -> Result<(), SceneFileError> {
+        for entry in &self.scene_file.nodes {
+            let description = &entry.description;
+            if scene.find_node(&description.name).is_some() {
+                return Err(self.error_at(
+                    entry,
+                    format!("node '{}' is defined more than once", description.name),
+                ));
+            }
+            let parent = description
+                .parent
+                .as_ref()
+                .map(|parent| {
+                    scene.find_node(parent).ok_or_else(|| {
+                        self.error_at(entry, format!("unknown parent node '{parent}'"))
+                    })
+                })
+                .transpose()?;
+            let local_transform = Self::create_transform(
+                description.position,
+                description.rotation,
+                &description.scale,
+            )
+            .map_err(|message| self.error_at(entry, message))?;
+            scene.add_node(&description.name, parent, local_transform);
+        }
+
+        Ok(())
+    }
+
+    /// Turns the `[[tracks]]` into the animation of the scene, their nodes have to exist already.
+    fn create_animation(&self, scene: &Scenegraph<'_>) -> Result<Animation, SceneFileError> {
+        let mut animation = Animation::new();
+
+        for entry in &self.scene_file.tracks {
+            let error = |message: String| self.error_at(entry, message);
+
+            let track = match &entry.description {
+                TrackDescription::Node { node, property, interpolation, keys } => {
+                    let node = scene
+                        .find_node(node)
+                        .ok_or_else(|| error(format!("unknown node '{node}'")))?;
+                    match property {
+                        NodePropertyDescription::Position => AnimationTrack::NodePosition(
+                            node,
+                            Self::create_track(
+                                keys,
+                                *interpolation,
+                                KeyValueDescription::get_vector,
+                            )
+                            .map_err(error)?,
+                        ),
+                        NodePropertyDescription::Rotation => AnimationTrack::NodeRotation(
+                            node,
+                            Self::create_track(
+                                keys,
+                                *interpolation,
+                                KeyValueDescription::get_vector,
+                            )
+                            .map_err(error)?,
+                        ),
+                        // A single number scales uniformly
+                        NodePropertyDescription::Scale => AnimationTrack::NodeScale(
+                            node,
+                            Self::create_track(keys, *interpolation, |value| match value {
+                                KeyValueDescription::Scalar(scale) => {
+                                    check_scale(Vec3::splat(*scale))
+                                }
+                                KeyValueDescription::Vector(scale) => {
+                                    check_scale(Vec3::from_array(*scale))
+                                }
+                            })
+                            .map_err(error)?,
+                        ),
+                    }
+                }
+                TrackDescription::Light { light, property, interpolation, keys } => {
+                    if *light >= self.scene_file.lights.len() {
+                        return Err(error(format!(
+                            "light {light} does not exist, there are {} lights",
+                            self.scene_file.lights.len()
+                        )));
+                    }
+                    match property {
+                        LightPropertyDescription::Color => AnimationTrack::LightColor(
+                            *light,
+                            Self::create_track(
+                                keys,
+                                *interpolation,
+                                KeyValueDescription::get_vector,
+                            )
+                            .map_err(error)?,
+                        ),
+                        LightPropertyDescription::Intensity => AnimationTrack::LightIntensity(
+                            *light,
+                            Self::create_track(
+                                keys,
+                                *interpolation,
+                                KeyValueDescription::get_scalar,
+                            )
+                            .map_err(error)?,
+                        ),
+                    }
+                }
+                TrackDescription::Camera { property, interpolation, keys } => match property {
+                    CameraPropertyDescription::Position => AnimationTrack::CameraPosition(
+                        Self::create_track(keys, *interpolation, KeyValueDescription::get_vector)
+                            .map_err(error)?,
+                    ),
+                    CameraPropertyDescription::Target => AnimationTrack::CameraTarget(
+                        Self::create_track(keys, *interpolation, KeyValueDescription::get_vector)
+                            .map_err(error)?,
+                    ),
+                    CameraPropertyDescription::Fov => AnimationTrack::CameraFov(
+                        Self::create_track(keys, *interpolation, KeyValueDescription::get_scalar)
+                            .map_err(error)?,
+                    ),
+                },
+            };
+
+            animation.add_track(track);
+        }
+
+        if let Some(description) = &self.scene_file.animation {
+            if let Some(duration) = description.duration {
+                animation.set_duration(duration);
+            }
+            animation.set_looping(description.is_looping);
+            animation.set_playing(description.autoplay);
+        }
+
+        Ok(animation)
+    }
+
+    fn create_track<T: Animatable>(
+        keys: &[KeyframeDescription],
+        interpolation: InterpolationDescription,
+        get_value: impl Fn(&KeyValueDescription) -> Result<T, String>,
+    ) -> Result<Track<T>, String> {
+        if keys.is_empty() {
+            return Err("a track needs at least one key".to_string());
+        }
+
+        let keyframes = keys
+            .iter()
+            .map(|key| {
+                Ok(Keyframe::new(
+                    key.time,
+                    get_value(&key.value)?,
+                    key.interpolation.unwrap_or(interpolation).into(),
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Track::new(keyframes))
+    }
+
+    /// Scales, then rotates around the x, y and z axis and finally moves to the position.
+    fn create_transform(
+        position: [f32; 3],
+        rotation: [f32; 3],
+        scale: &ScaleDescription,
+    ) -> Result<Affine3A, String> {
+        Ok(Affine3A::from_scale_rotation_translation(
+            scale.get_vector()?,
+            rotation_from_euler_degrees(Vec3::from_array(rotation)),
+            Vec3::from_array(position),
+        ))
+    }
+
+    /// `obj_materials` maps the usemtl names of the OBJ file to materials of the manager.
+    fn load_mesh<'a>(
+        &self,
+        object_properties: ObjectProperties<'a>,
+        obj: &Path,
+        material_manager: &'a MaterialManager<'a>,
+        obj_materials: &HashMap<String, String>,
+        cull_mode: CullModeDescription,
+        crease_angle: Option<f32>,
+    ) -> Result<TriangleMesh<'a>, String> {
+        let materials = obj_materials
+            .iter()
+            .map(|(obj_material, material)| {
+                material_manager
+                    .get_material(material)
+                    .map(|material| (obj_material.as_str(), material))
+                    .ok_or_else(|| format!("unknown material '{material}'"))
+            })
+            .collect::<Result<HashMap<_, _>, String>>()?;
+        let mut triangle_mesh = TriangleMesh::new_from_obj(
+            object_properties,
+            &self.resolve_path(obj),
+            cull_mode.into(),
+            &materials,
+        )
+        .map_err(|obj_error| format!("failed to load mesh: {obj_error}"))?;
+        if let Some(crease_angle) = crease_angle {
+            triangle_mesh.generate_vertex_normals(crease_angle);
+        }
+        Ok(triangle_mesh)
+    }
+
+    fn resolve_color(
+        material_manager: &MaterialManager<'_>,
+        color: &ColorDescription,
+    ) -> Result<RGBColor, String> {
+        match color {
+            ColorDescription::Named(color_name) => material_manager
+                .get_diffuse_color(color_name)
+                .ok_or_else(|| format!("unknown color '{color_name}'")),
+            ColorDescription::Rgb(rgb) => Ok(Vec3::from_array(*rgb)),
+        }
+    }
+
+    /// Files are referred to relative to the scene file, absolute paths are used as they are.
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        self.path.parent().unwrap_or_else(|| Path::new("")).join(path)
+    }
+
+    fn error_at<T>(&self, entry: &SceneEntry<T>, message: String) -> SceneFileError {
+        SceneFileError { path: self.path.clone(), line: Some(entry.line), message }
+    }
+}
+
+/// A scale of 0 flattens the object and can't be undone to trace rays in object space.
+fn check_scale(scale: Vec3) -> Result<Vec3, String> {
+    if is_valid_scale(scale) {
+        Ok(scale)
+    } else {
+        Err(format!("scale has to be positive and finite, found {scale}"))
+    }
+}
+
+fn get_line_number(source: &str, byte_offset: usize) -> usize {
+    source[..byte_offset.min(source.len())].matches('\n').count() + 1
+}
@@ -1,4 +1,8 @@
 use super::scenegraph::Scenegraph;
+use crate::{
+    camera::Camera,
+    lights::{light_manager::LightManager, LightEnum},
+};
 
 pub struct SceneManager<'a> {
     pub scenes: Vec<Scenegraph<'a>>,
@@ -10,9 +14,19 @@ impl<'a> SceneManager<'a> {
         Self { scenes: Vec::new(), current_scene_index: 0 }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
-        self.scenes[self.current_scene_index].update(delta_time);
+    /// Plays the animation of the current scene, which may move the camera and change the
+    /// lights, and then updates its objects.
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        camera: &mut Camera,
+        light_manager: &mut LightManager<LightEnum>,
+    ) {
+        let scene = &mut self.scenes[self.current_scene_index];
+        scene.update_animation(delta_time, camera, light_manager);
+        scene.update(delta_time);
     }
+
     pub fn add_scene(&mut self, scene: Scenegraph<'a>) {
         self.scenes.push(scene);
     }
@@ -33,6 +47,15 @@ impl<'a> SceneManager<'a> {
         &mut self.scenes[self.current_scene_index]
     }
 
+    pub const fn num_scenes(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn set_current_scene(&mut self, index: usize) {
+        assert!(index < self.scenes.len(), "scene index {index} out of range");
+        self.current_scene_index = index;
+    }
+
     pub fn next_scene(&mut self) {
         self.current_scene_index += 1;
         if self.current_scene_index >= self.scenes.len() {
@@ -0,0 +1,90 @@
+use glam::{Affine3A, Quat, Vec3};
+
+/// Index of a node in its scenegraph.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NodeId(pub(super) usize);
+
+/// Named group in the scenegraph hierarchy. The objects of a node and all of its children move
+/// with its transform, which is relative to the parent node.
+pub struct SceneNode {
+    name: String,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    local_transform: Affine3A,
+    /// Local transform combined with the transforms of all parents, kept up to date by the
+    /// scenegraph
+    world_transform: Affine3A,
+    /// Indices of the objects of the scenegraph that belong to this node, together with their
+    /// transform relative to the node
+    pub(super) objects: Vec<(usize, Affine3A)>,
+}
+
+impl SceneNode {
+    pub(super) const MIN_ANIMATED_SCALE: f32 = 1e-4;
+
+    pub(super) fn new(name: &str, parent: Option<NodeId>, local_transform: Affine3A) -> Self {
+        Self {
+            name: name.to_string(),
+            parent,
+            children: Vec::new(),
+            local_transform,
+            world_transform: local_transform,
+            objects: Vec::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub const fn get_parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn get_children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    pub const fn get_local_transform(&self) -> &Affine3A {
+        &self.local_transform
+    }
+
+    pub const fn get_world_transform(&self) -> &Affine3A {
+        &self.world_transform
+    }
+
+    pub(super) fn add_child(&mut self, child: NodeId) {
+        self.children.push(child);
+    }
+
+    pub(super) const fn set_local_transform(&mut self, local_transform: Affine3A) {
+        self.local_transform = local_transform;
+    }
+
+    pub(super) fn set_position(&mut self, position: Vec3) {
+        let (scale, rotation, _) = self.local_transform.to_scale_rotation_translation();
+        self.local_transform = Affine3A::from_scale_rotation_translation(scale, rotation, position);
+    }
+
+    pub(super) fn set_rotation(&mut self, rotation: Quat) {
+        let (scale, _, position) = self.local_transform.to_scale_rotation_translation();
+        self.local_transform = Affine3A::from_scale_rotation_translation(scale, rotation, position);
+    }
+
+    /// Ignores scales that aren't positive and finite.
+    pub(super) fn set_scale(&mut self, scale: Vec3) {
+        if !is_valid_scale(scale) {
+            return;
+        }
+        let (_, rotation, position) = self.local_transform.to_scale_rotation_translation();
+        self.local_transform = Affine3A::from_scale_rotation_translation(scale, rotation, position);
+    }
+
+    pub(super) const fn set_world_transform(&mut self, world_transform: Affine3A) {
+        self.world_transform = world_transform;
+    }
+}
+
+pub fn is_valid_scale(scale: Vec3) -> bool {
+    scale.is_finite() && scale.min_element() > 0.0
+}
@@ -1,25 +1,205 @@
+use super::scene_node::{NodeId, SceneNode};
+use crate::animation::{Animation, AnimationTrack};
+use crate::camera::Camera;
 use crate::hitrecord::HitRecord;
+use crate::lights::{light_manager::LightManager, LightEnum};
+use crate::materials::material_properties::Material;
+use crate::math::{aabb::Aabb, rotation_from_euler_degrees};
+use crate::objects::bvh::Bvh;
+use crate::objects::instance::Instance;
 use crate::objects::object_properties::Object;
 use crate::objects::ObjectEnum;
 use crate::ray::Ray;
+use glam::{Affine3A, Vec3};
+use std::sync::Arc;
 
 pub struct Scenegraph<'mm> {
     objects: Vec<ObjectEnum<'mm>>,
+    // indices into `objects`, the position in `bounded_objects` is the primitive index in the bvh
+    bounded_objects: Vec<usize>,
+    unbounded_objects: Vec<usize>,
+    bvh: Bvh,
+    is_bvh_outdated: bool,
+    // parents always come before their children
+    nodes: Vec<SceneNode>,
+    are_transforms_outdated: bool,
+    animation: Animation,
+    revision: u64,
 }
 
 impl<'mm> Scenegraph<'mm> {
     pub const fn new() -> Self {
-        Scenegraph { objects: Vec::new() }
+        Scenegraph {
+            objects: Vec::new(),
+            bounded_objects: Vec::new(),
+            unbounded_objects: Vec::new(),
+            bvh: Bvh::empty(),
+            is_bvh_outdated: false,
+            nodes: Vec::new(),
+            are_transforms_outdated: false,
+            animation: Animation::new(),
+            revision: 0,
+        }
     }
 
     pub fn add_object(&mut self, object: ObjectEnum<'mm>) {
         self.objects.push(object);
+        self.is_bvh_outdated = true;
+        self.revision += 1;
     }
 
     pub fn remove_object(&mut self, object: &ObjectEnum<'mm>) {
         if let Some(index) = self.objects.iter().position(|o| std::ptr::eq(o, object)) {
             self.objects.remove(index);
+            for node in &mut self.nodes {
+                node.objects.retain(|&(object_index, _)| object_index != index);
+                for (object_index, _) in &mut node.objects {
+                    if *object_index > index {
+                        *object_index -= 1;
+                    }
+                }
+            }
+            self.is_bvh_outdated = true;
+            self.revision += 1;
+        }
+    }
+
+    /// Adds a named node below `parent`, or at the root of the hierarchy, with a transform
+    /// relative to its parent.
+    pub fn add_node(
+        &mut self,
+        name: &str,
+        parent: Option<NodeId>,
+        local_transform: Affine3A,
+    ) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(SceneNode::new(name, parent, local_transform));
+        if let Some(parent) = parent {
+            self.nodes[parent.0].add_child(id);
+        }
+        self.are_transforms_outdated = true;
+        id
+    }
+
+    /// Adds an object that moves along with `node`. Its geometry is shared through an instance,
+    /// an object that already is an instance keeps its transform relative to the node.
+    pub fn add_object_to_node(&mut self, node: NodeId, object: ObjectEnum<'mm>) {
+        let (local_transform, material, geometry) = match object {
+            ObjectEnum::Instance(instance) => {
+                (*instance.get_transform(), instance.material, instance.geometry)
+            }
+            object => (Affine3A::IDENTITY, None, Arc::new(object)),
+        };
+
+        self.nodes[node.0].objects.push((self.objects.len(), local_transform));
+        self.add_object(ObjectEnum::Instance(Instance::new(geometry, local_transform, material)));
+        self.are_transforms_outdated = true;
+    }
+
+    pub fn find_node(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|node| node.get_name() == name).map(NodeId)
+    }
+
+    pub fn get_node(&self, node: NodeId) -> &SceneNode {
+        &self.nodes[node.0]
+    }
+
+    pub fn get_nodes(&self) -> impl Iterator<Item = (NodeId, &SceneNode)> {
+        self.nodes.iter().enumerate().map(|(index, node)| (NodeId(index), node))
+    }
+
+    /// Moves, rotates or scales the node together with all of its objects and children, the
+    /// world transforms follow on the next `update`.
+    pub fn set_local_transform(&mut self, node: NodeId, local_transform: Affine3A) {
+        self.nodes[node.0].set_local_transform(local_transform);
+        self.are_transforms_outdated = true;
+    }
+
+    /// Propagates the local transforms down the hierarchy and moves the objects of every node.
+    pub fn update_world_transforms(&mut self) {
+        for index in 0..self.nodes.len() {
+            let node = &self.nodes[index];
+            let local_transform = *node.get_local_transform();
+            let world_transform = node.get_parent().map_or(local_transform, |parent| {
+                *self.nodes[parent.0].get_world_transform() * local_transform
+            });
+
+            for &(object_index, object_transform) in &node.objects {
+                if let ObjectEnum::Instance(instance) = &mut self.objects[object_index] {
+                    instance.set_transform(world_transform * object_transform);
+                }
+            }
+            self.nodes[index].set_world_transform(world_transform);
         }
+
+        self.are_transforms_outdated = false;
+        self.revision += 1;
+    }
+
+    /// Increases every time objects were added, removed or moved, so renderers can tell when
+    /// their samples are stale.
+    pub const fn get_revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub const fn get_animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    pub const fn get_animation_mut(&mut self) -> &mut Animation {
+        &mut self.animation
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+    }
+
+    /// Plays the animation of the scene forward and applies its tracks to the nodes, the camera
+    /// and the lights whenever its time changed, also while scrubbing a paused animation.
+    pub fn update_animation(
+        &mut self,
+        delta_time: f32,
+        camera: &mut Camera,
+        light_manager: &mut LightManager<LightEnum>,
+    ) {
+        self.animation.advance(delta_time);
+        let Some(time) = self.animation.take_time_to_apply() else {
+            return;
+        };
+
+        let mut camera_target = None;
+        for track in self.animation.get_tracks() {
+            match track {
+                AnimationTrack::NodePosition(node, track) => {
+                    self.nodes[node.0].set_position(track.sample(time));
+                }
+                AnimationTrack::NodeRotation(node, track) => {
+                    self.nodes[node.0]
+                        .set_rotation(rotation_from_euler_degrees(track.sample(time)));
+                }
+                AnimationTrack::NodeScale(node, track) => {
+                    // A Bezier track can overshoot below 0 between its keys, shrink the node to
+                    // almost nothing there instead of leaving it at its last valid scale
+                    let min_scale = Vec3::splat(SceneNode::MIN_ANIMATED_SCALE);
+                    self.nodes[node.0].set_scale(track.sample(time).max(min_scale));
+                }
+                AnimationTrack::LightColor(light, track) => {
+                    light_manager.set_light_color(*light, track.sample(time));
+                }
+                AnimationTrack::LightIntensity(light, track) => {
+                    light_manager.set_light_intensity(*light, track.sample(time));
+                }
+                AnimationTrack::CameraPosition(track) => camera.set_position(track.sample(time)),
+                // Aimed once the camera is in place
+                AnimationTrack::CameraTarget(track) => camera_target = Some(track.sample(time)),
+                AnimationTrack::CameraFov(track) => camera.set_fov(track.sample(time)),
+            }
+        }
+
+        if let Some(target) = camera_target {
+            camera.set_target(target);
+        }
+        self.are_transforms_outdated = true;
     }
 
     pub const fn get_objects(&self) -> &Vec<ObjectEnum<'mm>> {
@@ -27,19 +207,141 @@ impl<'mm> Scenegraph<'mm> {
     }
 
     pub fn update(&mut self, delta_time: f32) {
+        if self.are_transforms_outdated {
+            self.update_world_transforms();
+        }
+
         for object in &mut self.objects {
             object.update(delta_time);
         }
+
+        if self.is_bvh_outdated {
+            self.rebuild_bvh();
+        } else {
+            self.refit_bvh();
+        }
+    }
+
+    /// Builds the top-level bvh from scratch. Called from `update` after objects were added or
+    /// removed, until then `hit` falls back to testing every object.
+    pub fn rebuild_bvh(&mut self) {
+        self.bounded_objects.clear();
+        self.unbounded_objects.clear();
+
+        let mut object_bounds = Vec::with_capacity(self.objects.len());
+        for (index, object) in self.objects.iter().enumerate() {
+            match object.get_bounding_box() {
+                Some(bounds) => {
+                    self.bounded_objects.push(index);
+                    object_bounds.push(bounds);
+                }
+                None => self.unbounded_objects.push(index),
+            }
+        }
+
+        self.bvh = Bvh::new(&object_bounds);
+        self.is_bvh_outdated = false;
+    }
+
+    fn refit_bvh(&mut self) {
+        let object_bounds: Vec<Aabb> = self
+            .bounded_objects
+            .iter()
+            .map(|&index| self.objects[index].get_bounding_box().unwrap_or_default())
+            .collect();
+
+        self.bvh.refit(&object_bounds);
+    }
+
+    /// Bounds enclosing every bounded object in the scene, `None` when the scene only holds
+    /// unbounded objects. Unbounded objects such as planes are not taken into account.
+    pub fn get_bounding_box(&self) -> Option<Aabb> {
+        let bounds = if self.is_bvh_outdated {
+            self.objects
+                .iter()
+                .filter_map(Object::get_bounding_box)
+                .fold(Aabb::EMPTY, |bounds, object_bounds| bounds.union(&object_bounds))
+        } else {
+            self.bvh.bounds()
+        };
+
+        (!bounds.is_empty()).then_some(bounds)
     }
 
+    pub fn has_unbounded_objects(&self) -> bool {
+        self.objects.iter().any(|object| object.get_bounding_box().is_none())
+    }
+
+    /// Finds the closest hit, or any hit for shadow rays. The normal of the closest hit already
+    /// includes the normal or bump map of its material.
     pub fn hit(&self, ray: &mut Ray, hit_record: &mut HitRecord<'mm>, is_shadow: bool) -> bool {
         // Reset the t value of the hit record to the maximum value of f32 every new frame check
         if !is_shadow {
             ray.t_max = f32::MAX;
         }
 
-        self.objects.iter().fold(false, |hit_anything, object| {
+        let is_hit = self.hit_geometry(ray, hit_record, is_shadow);
+        if is_hit && !is_shadow {
+            if let Some(material) = hit_record.material {
+                material.perturb_normal(hit_record, ray.direction);
+            }
+        }
+        is_hit
+    }
+
+    fn hit_geometry(
+        &self,
+        ray: &mut Ray,
+        hit_record: &mut HitRecord<'mm>,
+        is_shadow: bool,
+    ) -> bool {
+        if self.is_bvh_outdated {
+            return Self::hit_objects(self.objects.iter().enumerate(), ray, hit_record, is_shadow);
+        }
+
+        let mut hit_anything = Self::hit_objects(
+            self.unbounded_objects.iter().map(|&index| (index, &self.objects[index])),
+            ray,
+            hit_record,
+            is_shadow,
+        );
+
+        if hit_anything && is_shadow {
+            return true;
+        }
+
+        if self.bvh.hit(ray, is_shadow, |index, ray| {
+            let object_index = self.bounded_objects[index];
+            self.objects[object_index].hit(ray, hit_record, is_shadow).then(|| {
+                hit_record.object_id = Self::get_object_id(object_index);
+                hit_record.t
+            })
+        }) {
+            ray.t_max = hit_record.t;
+            hit_anything = true;
+        }
+
+        hit_anything
+    }
+
+    /// Object IDs start at 1 so 0 can stand for the background.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn get_object_id(object_index: usize) -> u32 {
+        object_index as u32 + 1
+    }
+
+    fn hit_objects<'a>(
+        objects: impl Iterator<Item = (usize, &'a ObjectEnum<'mm>)>,
+        ray: &mut Ray,
+        hit_record: &mut HitRecord<'mm>,
+        is_shadow: bool,
+    ) -> bool
+    where
+        'mm: 'a,
+    {
+        objects.fold(false, |hit_anything, (index, object)| {
             if object.hit(ray, hit_record, is_shadow) {
+                hit_record.object_id = Self::get_object_id(index);
                 ray.t_max = hit_record.t;
                 true
             } else {
@@ -7,7 +7,7 @@ use crate::{
         light_properties::{LightProperties, LightType},
         point_light::PointLight,
     },
-    materials::material_manager::{MaterialManager, RoughnessConstants},
+    materials::material_manager::{MaterialManager, RefractiveMedium, RoughnessConstants},
     objects::{
         object_properties::ObjectProperties,
         plane::Plane,
@@ -56,6 +56,11 @@ pub fn create_materials(material_manager: &mut MaterialManager<'_>) {
         3,
     );
     material_manager.add_phong_brdf_dielectric_material("SkyBlue", RoughnessConstants::Rough, 2);
+
+    // refractive
+    material_manager.add_dielectric_material("White", RefractiveMedium::Glass);
+    material_manager.add_dielectric_material("LightBlue", RefractiveMedium::Water);
+    material_manager.add_dielectric_material("White", RefractiveMedium::Diamond);
 }
 
 pub fn create_lights(light_manager: &mut LightManager<LightEnum>) {
@@ -157,14 +162,16 @@ pub fn create_scene_01<'a>(
 
     //scene.add_object(ObjectEnum::TriangleMesh(TriangleMesh::new_from_obj(
     //    ObjectProperties::new(Vec3::new(6.0, 0.0, 0.0), gold),
-    //    "lowpoly_bunny",
+    //    Path::new("assets/obj_models/lowpoly_bunny.obj"),
     //    CullMode::BackFace,
-    //)));
+    //    &HashMap::new(),
+    //).expect("lowpoly_bunny.obj should load")));
     //scene.add_object(ObjectEnum::TriangleMesh(TriangleMesh::new_from_obj(
     //    ObjectProperties::new(Vec3::new(-4.0, 0.0, 0.0), light_cyan_smooth),
-    //    "lowpoly_bunny",
+    //    Path::new("assets/obj_models/lowpoly_bunny.obj"),
     //    CullMode::BackFace,
-    //)));
+    //    &HashMap::new(),
+    //).expect("lowpoly_bunny.obj should load")));
 
     scene_manager.add_scene(scene);
 }
@@ -180,6 +187,12 @@ pub fn create_scene_02<'a>(
         material_manager.get_material("phong_brdf_SkyBlue_dielectric_Smooth_RE5").unwrap();
     let hot_pink_smooth =
         material_manager.get_material("phong_brdf_HotPink_dielectric_Smooth_RE5").unwrap();
+    let glass = material_manager.get_material("dielectric_White_Glass").unwrap();
+
+    scene.add_object(ObjectEnum::Sphere(Sphere::new(
+        ObjectProperties::new(Vec3::new(0.0, 1.0, 2.0), glass),
+        1.0,
+    )));
 
     scene.add_object(ObjectEnum::Sphere(Sphere::new(
         ObjectProperties::new(Vec3::new(-1.0, 4.0, 0.0), sky_blue_smooth),